@@ -0,0 +1,86 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use reporting::{warning, File, Renderer, Span, Styles};
+
+/// Generates a source of the given number of lines, resembling generated code.
+fn generated_source(lines: usize) -> String {
+    (0..lines)
+        .map(|idx| format!("    let value_{idx} = compute({idx}, \"generated\");\n"))
+        .collect()
+}
+
+/// The line/column lookup used before files kept a line index, which scans the whole source.
+fn scan_line_column(source: &str, offset: usize) -> (usize, usize) {
+    let mut line = 1;
+    let mut column = 1;
+
+    for (idx, char) in source.char_indices() {
+        if idx >= offset {
+            break;
+        }
+        if char == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+
+    (line, column)
+}
+
+/// Returns evenly spaced offsets throughout the source.
+fn offsets(source: &str, count: usize) -> Vec<usize> {
+    (0..count).map(|idx| idx * source.len() / count).collect()
+}
+
+fn line_column(c: &mut Criterion) {
+    let mut group = c.benchmark_group("line_column");
+    group.sample_size(10);
+    for lines in [1_000, 50_000] {
+        let file = File::new("generated.rs", generated_source(lines));
+        let offsets = offsets(file.source(), 1_000);
+
+        group.bench_with_input(BenchmarkId::new("scan", lines), &offsets, |b, offsets| {
+            b.iter(|| {
+                for &offset in offsets {
+                    black_box(scan_line_column(file.source(), offset));
+                }
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("indexed", lines),
+            &offsets,
+            |b, offsets| {
+                b.iter(|| {
+                    for &offset in offsets {
+                        black_box(file.line_column(offset));
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn render(c: &mut Criterion) {
+    let file = File::new("generated.rs", generated_source(50_000));
+    let styles = Styles::plain();
+    let reports = offsets(file.source(), 2_000)
+        .into_iter()
+        .map(|offset| {
+            warning!("unused variable").location(Span::new(file.clone(), offset..offset + 1))
+        })
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("render");
+    group.sample_size(10);
+    group.bench_function("2000_warnings", |b| {
+        b.iter(|| black_box(Renderer::new(&styles, &reports).to_string()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, line_column, render);
+criterion_main!(benches);
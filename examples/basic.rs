@@ -1,4 +1,4 @@
-use reporting::{error, note, File, Location, Renderer, Styles};
+use reporting::{error, File, Renderer, Span, Styles};
 
 fn main() {
     let file = File::new("test.txt", "import stds;");
@@ -8,11 +8,9 @@ fn main() {
         "{}",
         Renderer::new(
             &styles,
-            &[
-                error!("Could not find package `{}`", "stds")
-                    .location(Location::new(file.clone(), 7)),
-                note!("Perhaps you meant `std`?")
-            ]
+            &[error!("Could not find package `{}`", "stds")
+                .location(Span::new(file.clone(), 7..11))
+                .with_help("Perhaps you meant `std`?")]
         )
     );
 }
@@ -0,0 +1,19 @@
+//! Generates the README's `sample.svg`: `cargo run --example sample > sample.svg`.
+
+use reporting::{error, File, Renderer, Span, Styles};
+
+fn main() {
+    let file = File::new("test.txt", "import stds;");
+    let styles = Styles::styled();
+
+    print!(
+        "{}",
+        Renderer::new(
+            &styles,
+            &[error!("Could not find package `{}`", "stds")
+                .location(Span::new(file.clone(), 7..11))
+                .with_help("Perhaps you meant `std`?")]
+        )
+        .svg()
+    );
+}
@@ -0,0 +1,139 @@
+//! Automatic application of machine-applicable [Suggestion]s.
+
+use std::ops::Range;
+
+use crate::{apply_edits, Applicability, Edit, File, Report, Suggestion};
+
+/// The result of applying the suggestions of a set of reports to a [File].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fixed<'a> {
+    /// The file's source, with every applied suggestion's edits made.
+    pub source: String,
+
+    /// The suggestions which were applied, in the order they were given.
+    pub applied: Vec<&'a Suggestion>,
+
+    /// The suggestions which were skipped, because one of their edits overlaps an edit which was
+    /// already applied, or doesn't fall on character boundaries.  None of their edits are made.
+    pub skipped: Vec<&'a Suggestion>,
+}
+
+/// Applies the [`Applicability::MachineApplicable`] suggestions of the given reports to a file.
+///
+/// Suggestions are considered in the order of their reports, and are applied as a whole or not at
+/// all.  A suggestion is skipped if any of its edits overlaps an edit which was already applied,
+/// so the result only depends on the order of the reports.  Edits identical to one which was
+/// already applied don't conflict with it, and aren't made twice.  Edits to other files are
+/// ignored, and suggestions with no edits to the file are neither applied nor skipped.
+///
+/// ```
+/// # use reporting::{apply_suggestions, error, Applicability, Edit, File, Span, Suggestion};
+/// let file = File::new("test.txt", "import stds;\nimport fmt");
+/// let fix = |range, replacement| {
+///     Suggestion::new("", Applicability::MachineApplicable)
+///         .edit(Edit::new(Span::new(file.clone(), range), replacement))
+/// };
+///
+/// let reports = [
+///     error!("Could not find package `stds`").suggestion(fix(7..11, "std")),
+///     error!("Could not find package `stds`").suggestion(fix(7..12, "std;")),
+///     error!("Expected `;`").suggestion(fix(23..23, ";")),
+/// ];
+///
+/// let fixed = apply_suggestions(&file, &reports);
+/// assert_eq!(fixed.source, "import std;\nimport fmt;");
+/// assert_eq!(fixed.applied.len(), 2);
+/// assert_eq!(fixed.skipped, [&reports[1].suggestions[0]]);
+///
+/// let other = File::new("other.txt", "import stds;");
+/// let fixed = apply_suggestions(&other, &reports);
+/// assert_eq!(fixed.source, "import stds;");
+/// assert!(fixed.applied.is_empty() && fixed.skipped.is_empty());
+///
+/// let empty = Suggestion::new("Remove the import", Applicability::MachineApplicable);
+/// let reports = [error!("Unused import").suggestion(empty)];
+/// assert!(apply_suggestions(&file, &reports).applied.is_empty());
+/// ```
+pub fn apply_suggestions<'a>(file: &File, reports: &'a [Report]) -> Fixed<'a> {
+    let source = file.source();
+    // Kept sorted by span, with insertions at the same position in the order they were applied.
+    let mut edits = Vec::<&Edit>::new();
+    let mut applied = Vec::new();
+    let mut skipped = Vec::new();
+
+    let suggestions = reports
+        .iter()
+        .flat_map(|report| &report.suggestions)
+        .filter(|suggestion| suggestion.applicability == Applicability::MachineApplicable);
+    for suggestion in suggestions {
+        // Reports usually share the file, so it's only compared by value if it isn't the same one.
+        let mut suggested = suggestion
+            .edits
+            .iter()
+            .filter(|edit| std::ptr::eq(&*edit.span.file, file) || *edit.span.file == *file)
+            .collect::<Vec<_>>();
+        if suggested.is_empty() {
+            continue;
+        }
+        suggested.sort_by_key(|edit| (edit.span.start, edit.span.end));
+
+        let mut new_edits = Vec::<&Edit>::new();
+        let mut conflict = false;
+        for edit in suggested {
+            let old = &edits[neighbours(&edits, edit)];
+            let new = &new_edits[neighbours(&new_edits, edit)];
+            if old.contains(&edit) || new.contains(&edit) {
+                continue;
+            }
+
+            conflict = !source.is_char_boundary(edit.span.start)
+                || !source.is_char_boundary(edit.span.end)
+                || old.iter().chain(new).any(|other| overlaps(edit, other));
+            if conflict {
+                break;
+            }
+            new_edits.push(edit);
+        }
+
+        if conflict {
+            skipped.push(suggestion);
+        } else {
+            for edit in new_edits {
+                let key = (edit.span.start, edit.span.end);
+                let index =
+                    edits.partition_point(|other| (other.span.start, other.span.end) <= key);
+                edits.insert(index, edit);
+            }
+            applied.push(suggestion);
+        }
+    }
+
+    let (source, _) = apply_edits(source, 0..source.len(), &edits);
+
+    Fixed {
+        source,
+        applied,
+        skipped,
+    }
+}
+
+/// Returns the range of edits which touch or overlap the given edit, out of edits sorted by span
+/// which don't overlap each other.  Only these can be identical to it or overlap it.
+fn neighbours(edits: &[&Edit], edit: &Edit) -> Range<usize> {
+    // As the edits don't overlap, their ends are sorted as well as their starts.
+    let start = edits.partition_point(|other| other.span.end < edit.span.start);
+    let end = edits.partition_point(|other| other.span.start <= edit.span.end);
+    start..end
+}
+
+/// Returns `true` if two edits change any of the same text, or insert text inside a span the other
+/// replaces.
+fn overlaps(a: &Edit, b: &Edit) -> bool {
+    let (a, b) = (a.span.range(), b.span.range());
+    if a.is_empty() || b.is_empty() {
+        // Insertions only conflict with edits which replace text around them.
+        a.start > b.start && a.start < b.end || b.start > a.start && b.start < a.end
+    } else {
+        a.start < b.end && b.start < a.end
+    }
+}
@@ -0,0 +1,128 @@
+//! Diagnostics as GitHub Actions workflow commands.
+
+use crate::{Location, Report, Severity};
+
+/// A renderer for diagnostic reports as GitHub Actions workflow commands, which are shown as
+/// annotations on pull requests.
+///
+/// Each report is written as a command on its own line, such as
+/// `::error file=src/main.txt,line=1,col=8,endLine=1,endColumn=11::message`.  The location is
+/// taken from the report's primary span, whose end column is inclusive.  The messages of children
+/// are appended to the report's message, and its code is used as the annotation's title.
+/// [`Severity::Note`] reports are written as `notice`s.
+///
+/// ```
+/// # use reporting::{error, warning, File, GithubRenderer, Span};
+/// let file = File::new("src/main.txt", "import stds;");
+/// let reports = [
+///     error!("Could not find package `stds`")
+///         .location(Span::new(file.clone(), 7..11))
+///         .with_help("Perhaps you meant `std`?"),
+///     warning!("100% of packages are unused"),
+/// ];
+///
+/// assert_eq!(
+///     GithubRenderer::new(&reports).to_string(),
+///     "::error file=src/main.txt,line=1,col=8,endLine=1,endColumn=11::Could not find package `stds`%0Ahelp: Perhaps you meant `std`?\n\
+///      ::warning::100%25 of packages are unused\n"
+/// );
+/// ```
+///
+/// Columns are counted in characters.  A span which ends within a character ends at that
+/// character.
+///
+/// ```
+/// # use reporting::{warning, File, GithubRenderer, Span};
+/// let file = File::new("src/main.txt", "let 😀 = 1;");
+/// let reports = [warning!("Unused variable").location(Span::new(file.clone(), 4..6))];
+///
+/// assert_eq!(
+///     GithubRenderer::new(&reports).to_string(),
+///     "::warning file=src/main.txt,line=1,col=5,endLine=1,endColumn=5::Unused variable\n"
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GithubRenderer<'a> {
+    reports: &'a [Report],
+}
+
+impl<'a> GithubRenderer<'a> {
+    /// Creates a new [GithubRenderer] with the given reports.
+    pub const fn new(reports: &'a [Report]) -> Self {
+        Self { reports }
+    }
+}
+
+impl<'a> std::fmt::Display for GithubRenderer<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for report in self.reports {
+            let command = match report.severity {
+                Severity::Note => "notice",
+                Severity::Warning => "warning",
+                Severity::Error | Severity::Bug => "error",
+            };
+            write!(f, "::{command}")?;
+
+            let mut properties = Vec::new();
+            if let Some(span) = report.primary_span() {
+                let (line, col) = span.start_location().line_column();
+
+                // The end is the position of the span's last character, if it has one.  Spans may
+                // end within a character, so the end is rounded down to a character boundary.
+                let source = span.file.source();
+                let end = (0..=span.end)
+                    .rev()
+                    .find(|&end| source.is_char_boundary(end))
+                    .unwrap_or(0);
+                let last = source[..end]
+                    .char_indices()
+                    .next_back()
+                    .map_or(span.start, |(offset, _)| offset.max(span.start));
+                let (end_line, end_column) = Location::new(span.file(), last).line_column();
+
+                properties.push(("file", span.file.path().to_string()));
+                properties.push(("line", line.to_string()));
+                properties.push(("col", col.to_string()));
+                properties.push(("endLine", end_line.to_string()));
+                properties.push(("endColumn", end_column.to_string()));
+            }
+            if let Some(code) = &report.code {
+                properties.push(("title", code.clone()));
+            }
+            for (i, (name, value)) in properties.iter().enumerate() {
+                let separator = if i == 0 { ' ' } else { ',' };
+                write!(f, "{separator}{name}={}", Escape(value, true))?;
+            }
+
+            write!(f, "::{}", Escape(&report.message, false))?;
+            for child in &report.children {
+                let message = format!("\n{}: {}", child.kind.name(), child.message);
+                write!(f, "{}", Escape(&message, false))?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Text escaped for a workflow command.  Properties also have `:` and `,` escaped, as they
+/// separate a command's properties.
+struct Escape<'a>(&'a str, bool);
+
+impl std::fmt::Display for Escape<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Escape(text, property) = *self;
+        for c in text.chars() {
+            match c {
+                '%' => write!(f, "%25")?,
+                '\r' => write!(f, "%0D")?,
+                '\n' => write!(f, "%0A")?,
+                ':' if property => write!(f, "%3A")?,
+                ',' if property => write!(f, "%2C")?,
+                c => write!(f, "{c}")?,
+            }
+        }
+        Ok(())
+    }
+}
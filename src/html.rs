@@ -0,0 +1,199 @@
+//! Diagnostics rendered as HTML.
+
+use anstyle::{Ansi256Color, AnsiColor, Color, Effects, RgbColor, Style};
+
+use crate::{Markup, Output, Renderer, Styles};
+
+/// A renderer for diagnostic reports as HTML, created by [`Renderer::html`].
+///
+/// Reports are rendered with the same layout as the text [Renderer], inside a
+/// `<pre class="reporting">` element.  Styled text is wrapped in `<span>` elements, with the name
+/// of the field of [Styles] holding their style as their class, such as `<span class="error">`.
+/// A `<style>` element preceding the reports gives each class the colors and effects of its
+/// style, scoped to the `reporting` class.  Blinking and inverted text isn't supported.
+///
+/// ```
+/// # use reporting::{error, File, Renderer, Span, Styles};
+/// let file = File::new("test.txt", "let x = a < b;");
+/// let reports = [error!("Expected `>`").location(Span::new(file.clone(), 10..11))];
+///
+/// let styles = Styles::styled();
+/// let html = Renderer::new(&styles, &reports).html().to_string();
+/// assert!(html.starts_with("<style>\n.reporting .location { color: #ffffff; }\n"));
+/// assert!(html.contains(
+///     "<pre class=\"reporting\"><span class=\"error\">error</span><span class=\"colon\">:</span> \
+///      <span class=\"message\">Expected `&gt;`</span>\n"
+/// ));
+/// assert!(html.contains("<span class=\"snippet\">let x = a &lt; b;</span>"));
+/// assert!(html.ends_with("</pre>\n"));
+/// ```
+///
+/// Control characters, which can't be written in HTML or XML, are replaced with `\u{FFFD}`.
+///
+/// ```
+/// # use reporting::{error, Renderer, Styles};
+/// let reports = [error!("Unexpected `\x0c` or `\x1b`")];
+///
+/// let html = Renderer::new(&Styles::plain(), &reports).html().to_string();
+/// assert!(html.contains("Unexpected `\u{FFFD}` or `\u{FFFD}`"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HtmlRenderer<'a> {
+    renderer: Renderer<'a>,
+    stylesheet: bool,
+}
+
+impl<'a> HtmlRenderer<'a> {
+    /// Creates a new [HtmlRenderer] with the layout and settings of the given renderer.
+    pub const fn new(renderer: Renderer<'a>) -> Self {
+        Self {
+            renderer,
+            stylesheet: true,
+        }
+    }
+
+    /// Sets whether a `<style>` element is written before the reports, if any of the styles aren't
+    /// plain.  Defaults to `true`.  Leave it out to style the classes of the reports' elements with
+    /// a stylesheet of your own.
+    pub fn stylesheet(mut self, stylesheet: bool) -> Self {
+        self.stylesheet = stylesheet;
+        self
+    }
+}
+
+impl<'a> std::fmt::Display for HtmlRenderer<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let styles = self.renderer.styles;
+        let css = stylesheet(styles, ".reporting", "color");
+        if self.stylesheet && !css.is_empty() {
+            write!(f, "<style>\n{css}</style>\n")?;
+        }
+
+        write!(f, "<pre class=\"reporting\">")?;
+        let markup = Markup {
+            styles,
+            element: "span",
+        };
+        self.renderer.fmt_reports(&mut Output::markup(f, markup))?;
+        writeln!(f, "</pre>")
+    }
+}
+
+/// Returns CSS rules giving each of the classes of marked up reports the colors and effects of
+/// its style.  Text is colored with the given property, and has a background color only if the
+/// property is `color`.
+pub(crate) fn stylesheet(styles: &Styles, scope: &str, color: &str) -> String {
+    let mut css = String::new();
+    for (class, style) in styles.fields() {
+        let declarations = declarations(style, color);
+        if !declarations.is_empty() {
+            css.push_str(&format!("{scope} .{class} {{ {declarations} }}\n"));
+        }
+    }
+    css
+}
+
+/// Returns the CSS declarations of a style.
+fn declarations(style: &Style, color: &str) -> String {
+    let mut declarations = Vec::new();
+    if let Some(fg) = style.get_fg_color() {
+        declarations.push(format!("{color}: {};", hex(fg)));
+    }
+    if let Some(bg) = style.get_bg_color().filter(|_| color == "color") {
+        declarations.push(format!("background-color: {};", hex(bg)));
+    }
+
+    let effects = style.get_effects();
+    if effects.contains(Effects::BOLD) {
+        declarations.push("font-weight: bold;".to_string());
+    }
+    if effects.contains(Effects::DIMMED) {
+        declarations.push("opacity: 0.7;".to_string());
+    }
+    if effects.contains(Effects::ITALIC) {
+        declarations.push("font-style: italic;".to_string());
+    }
+    if effects.contains(Effects::HIDDEN) {
+        declarations.push("visibility: hidden;".to_string());
+    }
+
+    let underline = [
+        (Effects::UNDERLINE, "solid"),
+        (Effects::DOUBLE_UNDERLINE, "double"),
+        (Effects::CURLY_UNDERLINE, "wavy"),
+        (Effects::DOTTED_UNDERLINE, "dotted"),
+        (Effects::DASHED_UNDERLINE, "dashed"),
+    ]
+    .into_iter()
+    .find(|(effect, _)| effects.contains(*effect))
+    .map(|(_, decoration)| decoration);
+    let lines = [
+        underline.map(|_| "underline"),
+        Some("line-through").filter(|_| effects.contains(Effects::STRIKETHROUGH)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    if !lines.is_empty() {
+        declarations.push(format!("text-decoration-line: {};", lines.join(" ")));
+    }
+    if let Some(decoration) = underline {
+        declarations.push(format!("text-decoration-style: {decoration};"));
+        if let Some(underline) = style.get_underline_color() {
+            declarations.push(format!("text-decoration-color: {};", hex(underline)));
+        }
+    }
+
+    declarations.join(" ")
+}
+
+/// Returns a color as a CSS hex color.  ANSI colors are taken from xterm's default palette.
+pub(crate) fn hex(color: Color) -> String {
+    let RgbColor(r, g, b) = rgb(color);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Returns the RGB value of a color.  ANSI colors are taken from xterm's default palette.
+fn rgb(color: Color) -> RgbColor {
+    let index = match color {
+        Color::Rgb(rgb) => return rgb,
+        Color::Ansi(ansi) => Ansi256Color::from(ansi).0,
+        Color::Ansi256(Ansi256Color(index)) => index,
+    };
+
+    match index {
+        0..=15 => {
+            let ansi = Ansi256Color(index).into_ansi().unwrap();
+            let (r, g, b) = match ansi {
+                AnsiColor::Black => (0, 0, 0),
+                AnsiColor::Red => (205, 0, 0),
+                AnsiColor::Green => (0, 205, 0),
+                AnsiColor::Yellow => (205, 205, 0),
+                AnsiColor::Blue => (0, 0, 238),
+                AnsiColor::Magenta => (205, 0, 205),
+                AnsiColor::Cyan => (0, 205, 205),
+                AnsiColor::White => (229, 229, 229),
+                AnsiColor::BrightBlack => (127, 127, 127),
+                AnsiColor::BrightRed => (255, 0, 0),
+                AnsiColor::BrightGreen => (0, 255, 0),
+                AnsiColor::BrightYellow => (255, 255, 0),
+                AnsiColor::BrightBlue => (92, 92, 255),
+                AnsiColor::BrightMagenta => (255, 0, 255),
+                AnsiColor::BrightCyan => (0, 255, 255),
+                AnsiColor::BrightWhite => (255, 255, 255),
+            };
+            RgbColor(r, g, b)
+        }
+        // A 6x6x6 cube of colors.
+        16..=231 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let index = index - 16;
+            RgbColor(level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        // A ramp of grays, from dark to light.
+        232..=255 => {
+            let level = 8 + (index - 232) * 10;
+            RgbColor(level, level, level)
+        }
+    }
+}
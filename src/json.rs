@@ -0,0 +1,279 @@
+//! Diagnostics in the JSON format of rustc's `--error-format=json`.
+
+use crate::{
+    Applicability, ChildKind, LabelKind, Registry, Renderer, Report, Severity, Span, Styles,
+};
+
+/// A JSON value, written without whitespace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Number(usize),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(&'static str, Value)>),
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(value: usize) -> Self {
+        Value::Number(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::Number(value) => write!(f, "{value}"),
+            Value::String(value) => fmt_string(f, value),
+            Value::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    fmt_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Writes a string as a quoted JSON string, escaping quotes, backslashes and control characters.
+fn fmt_string(f: &mut std::fmt::Formatter<'_>, string: &str) -> std::fmt::Result {
+    write!(f, "\"")?;
+    for c in string.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// A renderer for diagnostic reports in the JSON format of rustc's `--error-format=json`, which is
+/// read by tools such as cargo and rust-analyzer.
+///
+/// Each report is written as a JSON object on its own line.  Suggestions are written as `help`
+/// children, with the replacement text on their spans.  Columns are counted in characters, and
+/// [`ChildKind::Info`] children are written as `note`s, as rustc has no equivalent.  The
+/// `rendered` text is rendered with the given styles.
+///
+/// ```
+/// # use reporting::{error, File, JsonRenderer, Span, Styles};
+/// let file = File::new("test.txt", "import stds;");
+/// let reports = [error!("Could not find package `stds`")
+///     .code("E0412")
+///     .location(Span::new(file.clone(), 7..11))];
+///
+/// let json = JsonRenderer::new(&Styles::plain(), &reports).to_string();
+/// assert!(json.starts_with(
+///     r#"{"$message_type":"diagnostic","message":"Could not find package `stds`","code":{"code":"E0412","explanation":null},"level":"error","spans":[{"file_name":"test.txt","byte_start":7,"byte_end":11,"line_start":1,"line_end":1,"column_start":8,"column_end":12,"is_primary":true,"#
+/// ));
+/// assert!(json.contains(r#""rendered":"error[E0412]: Could not find package `stds`\n --> test.txt:1:8\n"#));
+/// assert!(json.ends_with("}\n"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JsonRenderer<'a> {
+    styles: &'a Styles,
+    reports: &'a [Report],
+    registry: Option<&'a Registry>,
+}
+
+impl<'a> JsonRenderer<'a> {
+    /// Creates a new [JsonRenderer] with the given styles and reports.
+    pub const fn new(styles: &'a Styles, reports: &'a [Report]) -> Self {
+        Self {
+            styles,
+            reports,
+            registry: None,
+        }
+    }
+
+    /// Sets the registry from which the explanations of diagnostic codes are taken.  Defaults to
+    /// none, in which case every explanation is `null`.
+    pub fn registry(mut self, registry: &'a Registry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+}
+
+impl<'a> JsonRenderer<'a> {
+    /// Converts a report into a rustc diagnostic.
+    fn diagnostic(&self, report: &Report) -> Value {
+        let level = match report.severity {
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+            Severity::Bug => "error: internal compiler error",
+        };
+        let code = report.code.as_deref().map(|code| {
+            let explanation = self.registry.and_then(|registry| registry.get(code));
+            Value::Object(vec![
+                ("code", code.into()),
+                ("explanation", explanation.into()),
+            ])
+        });
+
+        let spans = Renderer::annotations(report)
+            .into_iter()
+            .map(|(kind, span, label)| json_span(span, kind == LabelKind::Primary, label, None))
+            .collect();
+
+        let children = report.children.iter().map(|child| {
+            let level = match child.kind {
+                ChildKind::Note | ChildKind::Info => "note",
+                ChildKind::Help => "help",
+            };
+            let spans = child
+                .location
+                .iter()
+                .map(|span| json_span(span, true, None, None))
+                .collect();
+            sub_diagnostic(&child.message, level, spans)
+        });
+        let suggestions = report.suggestions.iter().map(|suggestion| {
+            let spans = suggestion
+                .edits
+                .iter()
+                .map(|edit| {
+                    let replacement = (edit.replacement.as_str(), suggestion.applicability);
+                    json_span(&edit.span, true, None, Some(replacement))
+                })
+                .collect();
+            sub_diagnostic(&suggestion.message, "help", spans)
+        });
+
+        let rendered = Renderer::new(self.styles, std::slice::from_ref(report)).to_string();
+        Value::Object(vec![
+            ("$message_type", "diagnostic".into()),
+            ("message", report.message.as_str().into()),
+            ("code", code.into()),
+            ("level", level.into()),
+            ("spans", Value::Array(spans)),
+            (
+                "children",
+                Value::Array(children.chain(suggestions).collect()),
+            ),
+            ("rendered", rendered.into()),
+        ])
+    }
+}
+
+impl<'a> std::fmt::Display for JsonRenderer<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for report in self.reports {
+            writeln!(f, "{}", self.diagnostic(report))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Creates a rustc child diagnostic, which has no code, children or rendered text of its own.
+fn sub_diagnostic(message: &str, level: &str, spans: Vec<Value>) -> Value {
+    Value::Object(vec![
+        ("message", message.into()),
+        ("code", Value::Null),
+        ("level", level.into()),
+        ("spans", Value::Array(spans)),
+        ("children", Value::Array(Vec::new())),
+        ("rendered", Value::Null),
+    ])
+}
+
+/// Converts a span into a rustc span, with the text of every line it covers.
+fn json_span(
+    span: &Span,
+    is_primary: bool,
+    label: Option<&str>,
+    replacement: Option<(&str, Applicability)>,
+) -> Value {
+    let (line_start, column_start) = span.start_location().line_column();
+    let (line_end, column_end) = span.end_location().line_column();
+
+    let text = (line_start..=line_end)
+        .filter_map(|line| {
+            let text = span.file.line(line)?;
+            let highlight_start = if line == line_start { column_start } else { 1 };
+            let highlight_end = if line == line_end {
+                column_end
+            } else {
+                text.chars().count() + 1
+            };
+            Some(Value::Object(vec![
+                ("text", text.into()),
+                ("highlight_start", highlight_start.into()),
+                ("highlight_end", highlight_end.into()),
+            ]))
+        })
+        .collect();
+
+    let applicability = replacement.map(|(_, applicability)| match applicability {
+        Applicability::MachineApplicable => "MachineApplicable",
+        Applicability::MaybeIncorrect => "MaybeIncorrect",
+        Applicability::HasPlaceholders => "HasPlaceholders",
+        Applicability::Unspecified => "Unspecified",
+    });
+    Value::Object(vec![
+        ("file_name", span.file.path().into()),
+        ("byte_start", span.start.into()),
+        ("byte_end", span.end.into()),
+        ("line_start", line_start.into()),
+        ("line_end", line_end.into()),
+        ("column_start", column_start.into()),
+        ("column_end", column_end.into()),
+        ("is_primary", is_primary.into()),
+        ("text", Value::Array(text)),
+        ("label", label.into()),
+        (
+            "suggested_replacement",
+            replacement.map(|(replacement, _)| replacement).into(),
+        ),
+        ("suggestion_applicability", applicability.into()),
+        ("expansion", Value::Null),
+    ])
+}
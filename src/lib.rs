@@ -1,7 +1,7 @@
 //! Simple diagnostic reporting for compilers.
 //!
 //! ```
-//! use reporting::{error, note, File, Location, Renderer, Styles};
+//! use reporting::{error, File, Renderer, Span, Styles};
 //!
 //! let file = File::new("test.txt", "import stds;");
 //! let styles = Styles::styled();
@@ -12,21 +12,133 @@
 //!         &styles,
 //!         &[
 //!             error!("Could not find package `{}`", "stds")
-//!                 .location(Location::new(file.clone(), 7)),
-//!             note!("Perhaps you meant `std`?")
+//!                 .location(Span::new(file.clone(), 7..11))
+//!                 .with_help("Perhaps you meant `std`?")
 //!         ]
 //!     )
 //! );
 //! ```
 
-use std::sync::Arc;
+use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    collections::BTreeMap,
+    fmt::Write as _,
+    io::{IsTerminal, Write},
+    ops::Range,
+    sync::Arc,
+};
 
-use anstyle::{AnsiColor, Color, Reset, Style};
-use unicode_width::UnicodeWidthChar;
+use anstyle::{Ansi256Color, AnsiColor, Color, RgbColor, Style};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 pub use anstyle;
+pub use fix::{apply_suggestions, Fixed};
+pub use github::GithubRenderer;
+pub use html::HtmlRenderer;
+pub use json::JsonRenderer;
+pub use registry::{Explanation, Registry};
+pub use sarif::SarifRenderer;
+pub use svg::SvgRenderer;
+#[cfg(feature = "theme")]
+pub use theme::ThemeError;
+
+mod fix;
+mod github;
+mod html;
+mod json;
+#[cfg(feature = "lsp")]
+pub mod lsp;
+mod registry;
+mod sarif;
+mod svg;
+#[cfg(feature = "theme")]
+mod theme;
+
+/// The unit in which column numbers are counted.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub enum ColumnUnit {
+    /// Unicode scalar values, as counted by [`str::chars`].
+    #[default]
+    Char,
+    /// Bytes of UTF-8, as used by gcc-style tools.
+    Byte,
+    /// UTF-16 code units, as used by the Language Server Protocol.
+    Utf16,
+    /// Terminal cells, as displayed by editors.
+    DisplayWidth,
+    /// Extended grapheme clusters, as perceived by the reader.
+    Grapheme,
+}
+
+impl ColumnUnit {
+    /// Splits text into the smallest pieces which can be measured in this unit.
+    fn pieces(self, text: &str) -> Box<dyn Iterator<Item = &str> + '_> {
+        match self {
+            ColumnUnit::Char | ColumnUnit::Byte | ColumnUnit::Utf16 => {
+                Box::new(text.split_inclusive(|_| true))
+            }
+            ColumnUnit::DisplayWidth | ColumnUnit::Grapheme => Box::new(text.graphemes(true)),
+        }
+    }
+
+    /// Returns the length of a piece of text in this unit.
+    fn piece_len(self, piece: &str) -> usize {
+        match self {
+            ColumnUnit::Char => piece.chars().count(),
+            ColumnUnit::Byte => piece.len(),
+            ColumnUnit::Utf16 => piece.encode_utf16().count(),
+            ColumnUnit::DisplayWidth => piece.width(),
+            ColumnUnit::Grapheme => 1,
+        }
+    }
+
+    /// Returns the length of the given text in this unit.
+    fn len(self, text: &str) -> usize {
+        self.pieces(text).map(|piece| self.piece_len(piece)).sum()
+    }
+}
+
+/// The line break convention used by a [File].
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum LineEnding {
+    /// Lines end with `\n`, as on Unix.
+    Lf,
+    /// Lines end with `\r\n`, as on Windows.
+    CrLf,
+    /// Lines end with a lone `\r`, as on classic Mac OS.
+    Cr,
+    /// Lines end with a mix of the above.
+    Mixed,
+}
 
 /// Information about a file.
+///
+/// Any of `\n`, `\r\n` or a lone `\r` is treated as a line break, and a leading byte order mark
+/// is ignored when counting columns.  Snippets are shown without either.
+///
+/// ```
+/// # use reporting::{error, File, Renderer, Span, Styles};
+/// let file = File::new("test.txt", "\u{feff}let x = 1;\r\nlet y = 2;\rlet z = 3;\r\n");
+/// let reports = [error!("Unused variables")
+///     .location(Span::new(file.clone(), 7..8))
+///     .secondary_label(Span::new(file.clone(), 19..20), "")
+///     .secondary_label(Span::new(file.clone(), 30..31), "")];
+///
+/// assert_eq!(
+///     Renderer::new(&Styles::plain(), &reports).to_string(),
+///     "error: Unused variables\n\
+///      \x20--> test.txt:1:5\n\
+///      \x20 |\n\
+///      1 | let x = 1;\n\
+///      \x20 |     ^\n\
+///      2 | let y = 2;\n\
+///      \x20 |     -\n\
+///      3 | let z = 3;\n\
+///      \x20 |     -\n"
+/// );
+/// ```
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct File {
     /// The file path, used for identifying the file in diagnostic reports.
@@ -34,14 +146,44 @@ pub struct File {
 
     /// The contents of the file.  Used to display snippets in diagnostic reports.
     source: String,
+
+    /// The byte offset at which each line of the source starts.  Used to look up line and column
+    /// numbers without rescanning the source.
+    line_starts: Vec<usize>,
+
+    /// The line break convention used by the source, or `None` if it has no line breaks.
+    line_ending: Option<LineEnding>,
 }
 
 impl File {
     /// Creates a new `File` with the given path and source.
     pub fn new(path: impl Into<String>, source: impl Into<String>) -> Arc<Self> {
+        let source = source.into();
+        let bytes = source.as_bytes();
+
+        let mut line_starts = vec![0];
+        let mut line_ending = None;
+        for (idx, &byte) in bytes.iter().enumerate() {
+            let ending = match byte {
+                b'\n' if idx > 0 && bytes[idx - 1] == b'\r' => LineEnding::CrLf,
+                b'\n' => LineEnding::Lf,
+                b'\r' if bytes.get(idx + 1) != Some(&b'\n') => LineEnding::Cr,
+                _ => continue,
+            };
+
+            line_starts.push(idx + 1);
+            line_ending = match line_ending {
+                None => Some(ending),
+                Some(line_ending) if line_ending == ending => Some(ending),
+                Some(_) => Some(LineEnding::Mixed),
+            };
+        }
+
         Arc::new(Self {
             path: path.into(),
-            source: source.into(),
+            source,
+            line_starts,
+            line_ending,
         })
     }
 
@@ -64,28 +206,156 @@ impl File {
         &self.source
     }
 
+    /// Returns the line break convention used by the file's source, or `None` if it has no line
+    /// breaks.
+    ///
+    /// ```
+    /// # use reporting::{File, LineEnding};
+    /// assert_eq!(File::new("a.txt", "hello\r\nworld\r\n").line_ending(), Some(LineEnding::CrLf));
+    /// assert_eq!(File::new("b.txt", "hello\nworld\r").line_ending(), Some(LineEnding::Mixed));
+    /// assert_eq!(File::new("c.txt", "hello").line_ending(), None);
+    /// ```
+    #[inline]
+    pub fn line_ending(&self) -> Option<LineEnding> {
+        self.line_ending
+    }
+
+    /// Returns `true` if the file's source starts with a byte order mark.
+    #[inline]
+    pub fn has_bom(&self) -> bool {
+        self.source.starts_with('\u{feff}')
+    }
+
+    /// Returns the number of lines in the file's source.  A trailing line break starts a final,
+    /// empty line.
+    #[inline]
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Returns the byte range of the given line (starting at `1`), excluding its line break and
+    /// any byte order mark.
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        let mut start = *self.line_starts.get(line.checked_sub(1)?)?;
+        if line == 1 && self.has_bom() {
+            start = '\u{feff}'.len_utf8();
+        }
+
+        let Some(&next) = self.line_starts.get(line) else {
+            return Some(start..self.source.len());
+        };
+        let bytes = self.source.as_bytes();
+        if next - 1 > start && bytes[next - 1] == b'\n' && bytes[next - 2] == b'\r' {
+            Some(start..next - 2)
+        } else {
+            Some(start..next - 1)
+        }
+    }
+
+    /// Returns the text of the given line (starting at `1`), excluding its line break.
+    ///
+    /// ```
+    /// # use reporting::File;
+    /// let my_file = File::new("test.txt", "hello\nworld\n");
+    ///
+    /// assert_eq!(my_file.line(2), Some("world"));
+    /// assert_eq!(my_file.line(3), Some(""));
+    /// assert_eq!(my_file.line(4), None);
+    /// ```
+    pub fn line(&self, line: usize) -> Option<&str> {
+        self.line_range(line).map(|range| &self.source[range])
+    }
+
+    /// Returns the line number containing the given offset, without checking that it is in bounds.
+    fn line_number(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= offset)
+    }
+
+    /// Returns the number of the last line with any content, ignoring the empty line after a
+    /// trailing line break.
+    fn last_line(&self) -> usize {
+        self.line_number(self.source.len().saturating_sub(1))
+    }
+
     /// Returns the line and column number corresponding to the given offset in the file's source.
+    /// Columns are counted in [`ColumnUnit::Char`]s.
     pub fn line_column(&self, offset: usize) -> Option<(usize, usize)> {
+        self.line_column_in(offset, ColumnUnit::Char)
+    }
+
+    /// Returns the line and column number corresponding to the given offset in the file's source,
+    /// with columns counted in the given unit.
+    ///
+    /// ```
+    /// # use reporting::{ColumnUnit, File};
+    /// let my_file = File::new("test.txt", "let 😀 = 1;");
+    ///
+    /// assert_eq!(my_file.line_column_in(9, ColumnUnit::Char), Some((1, 7)));
+    /// assert_eq!(my_file.line_column_in(9, ColumnUnit::Byte), Some((1, 10)));
+    /// assert_eq!(my_file.line_column_in(9, ColumnUnit::Utf16), Some((1, 8)));
+    /// assert_eq!(my_file.line_column_in(9, ColumnUnit::DisplayWidth), Some((1, 8)));
+    /// ```
+    pub fn line_column_in(&self, offset: usize, unit: ColumnUnit) -> Option<(usize, usize)> {
         if offset > self.source().len() {
             return None;
         }
 
-        let mut line = 1;
-        let mut column = 1;
+        // Offsets within a character are counted as being past it.
+        let mut end = offset;
+        while !self.source.is_char_boundary(end) {
+            end += 1;
+        }
+
+        let line = self.line_number(offset);
+        let line_start = self.line_range(line).unwrap().start;
+        let column = unit.len(&self.source[line_start.min(end)..end]) + 1;
+
+        Some((line, column))
+    }
+
+    /// Returns the offset in the file's source corresponding to the given line and column number,
+    /// or `None` if they are out of bounds.  This is the inverse of [`File::line_column`].  The
+    /// column may point one past the end of the line.
+    ///
+    /// ```
+    /// # use reporting::File;
+    /// let my_file = File::new("test.txt", "hello\nworld");
+    ///
+    /// assert_eq!(my_file.offset_of(2, 1), Some(6));
+    /// assert_eq!(my_file.offset_of(2, 6), Some(11));
+    /// assert_eq!(my_file.offset_of(2, 7), None);
+    /// assert_eq!(my_file.offset_of(3, 1), None);
+    /// ```
+    pub fn offset_of(&self, line: usize, column: usize) -> Option<usize> {
+        self.offset_of_in(line, column, ColumnUnit::Char)
+    }
+
+    /// Returns the offset in the file's source corresponding to the given line and column number,
+    /// with columns counted in the given unit.  Returns `None` if they are out of bounds, or if
+    /// the column falls within a piece of text that can't be split, such as a character in the
+    /// middle of a grapheme cluster.
+    ///
+    /// ```
+    /// # use reporting::{ColumnUnit, File};
+    /// let my_file = File::new("test.txt", "let 😀 = 1;");
+    ///
+    /// assert_eq!(my_file.offset_of_in(1, 8, ColumnUnit::Utf16), Some(9));
+    /// assert_eq!(my_file.offset_of_in(1, 6, ColumnUnit::Utf16), None);
+    /// ```
+    pub fn offset_of_in(&self, line: usize, column: usize, unit: ColumnUnit) -> Option<usize> {
+        let range = self.line_range(line)?;
+        let mut current = 1;
+        let mut offset = range.start;
 
-        for (idx, char) in self.source().char_indices() {
-            if idx >= offset {
+        for piece in unit.pieces(&self.source[range.clone()]) {
+            if current >= column {
                 break;
             }
-            if char == '\n' {
-                line += 1;
-                column = 1;
-            } else {
-                column += 1;
-            }
+            current += unit.piece_len(piece);
+            offset += piece.len();
         }
 
-        Some((line, column))
+        (current == column).then_some(offset)
     }
 }
 
@@ -115,6 +385,33 @@ impl Location {
         }
     }
 
+    /// Attempts to create a `Location` from a line and column number in the given file, returning
+    /// `None` if they are out of bounds.
+    ///
+    /// ```
+    /// # use reporting::{File, Location};
+    /// let my_file = File::new("test.txt", "hello\nworld");
+    ///
+    /// let location = Location::from_line_column(my_file.clone(), 2, 3).unwrap();
+    /// assert_eq!(location.offset(), 8);
+    /// assert!(Location::from_line_column(my_file.clone(), 0, 1).is_none());
+    /// ```
+    pub fn from_line_column(file: Arc<File>, line: usize, column: usize) -> Option<Self> {
+        Self::from_line_column_in(file, line, column, ColumnUnit::Char)
+    }
+
+    /// Attempts to create a `Location` from a line and column number in the given file, with
+    /// columns counted in the given unit.  Returns `None` if they are out of bounds.
+    pub fn from_line_column_in(
+        file: Arc<File>,
+        line: usize,
+        column: usize,
+        unit: ColumnUnit,
+    ) -> Option<Self> {
+        let offset = file.offset_of_in(line, column, unit)?;
+        Some(Location { file, offset })
+    }
+
     /// Returns the file that contains this source location.
     #[inline]
     pub fn file(&self) -> Arc<File> {
@@ -139,10 +436,35 @@ impl Location {
     /// assert_eq!((line2, column2), (2, 1));
     /// ```
     pub fn line_column(&self) -> (usize, usize) {
-        self.file()
-            .line_column(self.offset())
+        self.line_column_in(ColumnUnit::Char)
+    }
+
+    /// Returns the line and column number of this source location within its file, with columns
+    /// counted in the given unit.
+    pub fn line_column_in(&self, unit: ColumnUnit) -> (usize, usize) {
+        self.file
+            .line_column_in(self.offset(), unit)
             .expect("Offset should not be out of file's bounds")
     }
+
+    /// Returns an object which displays this location as `path:line:column`, with columns counted
+    /// in the given unit.  The [`Display`](std::fmt::Display) implementation of `Location` counts
+    /// columns in [`ColumnUnit::Char`]s.
+    ///
+    /// ```
+    /// # use reporting::{ColumnUnit, File, Location};
+    /// let my_file = File::new("test.txt", "é = 1;");
+    ///
+    /// let location = Location::new(my_file.clone(), 2);
+    /// assert_eq!(location.to_string(), "test.txt:1:2");
+    /// assert_eq!(location.display(ColumnUnit::Byte).to_string(), "test.txt:1:3");
+    /// ```
+    pub fn display(&self, unit: ColumnUnit) -> impl std::fmt::Display + '_ {
+        LocationDisplay {
+            location: self,
+            unit,
+        }
+    }
 }
 
 impl std::fmt::Debug for Location {
@@ -153,8 +475,174 @@ impl std::fmt::Debug for Location {
 
 impl std::fmt::Display for Location {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let (line, column) = self.line_column();
-        write!(f, "{}:{}:{}", self.file().path(), line, column)
+        self.display(ColumnUnit::Char).fmt(f)
+    }
+}
+
+/// Displays a [Location] with columns counted in a specific unit.
+struct LocationDisplay<'a> {
+    location: &'a Location,
+    unit: ColumnUnit,
+}
+
+impl std::fmt::Display for LocationDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (line, column) = self.location.line_column_in(self.unit);
+        write!(f, "{}:{}:{}", self.location.file.path(), line, column)
+    }
+}
+
+/// A range of bytes in a file.
+///
+/// Spans covering multiple lines are rendered in full, connected by a bar in the left margin.
+/// The bar starts with a `/` if the span starts at the beginning of a line's code, and otherwise
+/// with a line to the span's first character.
+///
+/// ```
+/// # use reporting::{error, File, Span, Styles};
+/// let file = File::new("test.txt", "fn main() {\n    let x = (1,\n        2);\n}\n");
+/// let report = error!("Unused tuple")
+///     .primary_label(Span::new(file.clone(), 24..38), "this tuple")
+///     .secondary_label(Span::new(file.clone(), 0..41), "in this function");
+///
+/// assert_eq!(
+///     report.render(&Styles::plain()).to_string(),
+///     "error: Unused tuple\n\
+///      \x20--> test.txt:2:13\n\
+///      \x20 |\n\
+///      1 | /  fn main() {\n\
+///      2 | |      let x = (1,\n\
+///      \x20 | | _____________^\n\
+///      3 | ||         2);\n\
+///      \x20 | ||__________^ this tuple\n\
+///      4 | |  }\n\
+///      \x20 | |__- in this function\n"
+/// );
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct Span {
+    file: Arc<File>,
+    start: usize,
+    end: usize,
+}
+
+impl Span {
+    /// Creates a new `Span` covering the given byte range of the file.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds for the file's source, or if its start is greater than
+    /// its end.
+    pub fn new(file: Arc<File>, range: Range<usize>) -> Self {
+        Self::try_new(file, range).expect("Range should be within the file's bounds")
+    }
+
+    /// Attempts to create a `Span` covering the given byte range of the file, returning `None` if
+    /// the range is out of bounds or its start is greater than its end.
+    pub fn try_new(file: Arc<File>, range: Range<usize>) -> Option<Self> {
+        if range.start > range.end || range.end > file.source().len() {
+            None
+        } else {
+            Some(Span {
+                file,
+                start: range.start,
+                end: range.end,
+            })
+        }
+    }
+
+    /// Returns the file that contains this span.
+    #[inline]
+    pub fn file(&self) -> Arc<File> {
+        self.file.clone()
+    }
+
+    /// Returns the byte offset where this span starts.
+    #[inline]
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the byte offset where this span ends (exclusive).
+    #[inline]
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Returns the byte range covered by this span.
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Returns the length of this span in bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Returns `true` if this span covers no bytes.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns the location where this span starts.
+    pub fn start_location(&self) -> Location {
+        Location {
+            file: self.file(),
+            offset: self.start,
+        }
+    }
+
+    /// Returns the location where this span ends.
+    pub fn end_location(&self) -> Location {
+        Location {
+            file: self.file(),
+            offset: self.end,
+        }
+    }
+
+    /// Returns the line and column number where this span starts.
+    ///
+    /// ```
+    /// # use reporting::{File, Span};
+    /// let my_file = File::new("test.txt", "hello\nworld");
+    ///
+    /// let span = Span::new(my_file.clone(), 6..11);
+    /// assert_eq!(span.line_column(), (2, 1));
+    /// assert_eq!(span.len(), 5);
+    /// ```
+    pub fn line_column(&self) -> (usize, usize) {
+        self.start_location().line_column()
+    }
+}
+
+impl From<Location> for Span {
+    /// Creates an empty span at the given location.
+    fn from(location: Location) -> Self {
+        Span {
+            start: location.offset,
+            end: location.offset,
+            file: location.file,
+        }
+    }
+}
+
+impl From<Location> for Option<Span> {
+    fn from(location: Location) -> Self {
+        Some(location.into())
+    }
+}
+
+impl std::fmt::Debug for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}..{}", self.file().path(), self.start, self.end)
+    }
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.start_location().fmt(f)
     }
 }
 
@@ -167,21 +655,237 @@ pub enum Severity {
     Bug,
 }
 
+impl Severity {
+    /// Returns the name of this severity, as written in headers.
+    fn name(self) -> &'static str {
+        match self {
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+            Severity::Bug => "bug",
+        }
+    }
+}
+
+/// The kind of a [Label], which determines how its span is underlined.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum LabelKind {
+    /// The cause of a diagnostic, underlined with `^`.
+    Primary,
+    /// Additional context for a diagnostic, underlined with `-`.
+    Secondary,
+}
+
+impl LabelKind {
+    /// Returns the character used to underline spans of this kind.
+    fn underline(self) -> char {
+        match self {
+            LabelKind::Primary => '^',
+            LabelKind::Secondary => '-',
+        }
+    }
+}
+
+/// A span annotated with a short message, which is displayed beneath the span's underline.
+/// Messages of multiple lines are displayed with each line beneath the last.
+///
+/// ```
+/// # use reporting::{error, File, Span, Styles};
+/// let file = File::new("test.txt", "let x = f(a);");
+/// let report = error!("Mismatched types")
+///     .secondary_label(Span::new(file.clone(), 4..5), "declared as `i32`\nhere")
+///     .primary_label(Span::new(file.clone(), 10..11), "expected `i32`,\nfound `str`");
+///
+/// assert_eq!(
+///     report.render(&Styles::plain()).to_string(),
+///     "error: Mismatched types\n\
+///      \x20--> test.txt:1:11\n\
+///      \x20 |\n\
+///      1 | let x = f(a);\n\
+///      \x20 |     -     ^ expected `i32`,\n\
+///      \x20 |     |       found `str`\n\
+///      \x20 |     |\n\
+///      \x20 |     declared as `i32`\n\
+///      \x20 |     here\n"
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Label {
+    pub kind: LabelKind,
+    pub span: Span,
+    pub message: String,
+}
+
+impl Label {
+    /// Creates a new `Label` with the given kind, span and message.
+    pub fn new(kind: LabelKind, span: impl Into<Span>, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            span: span.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Creates a [`LabelKind::Primary`] label with the given span and message.
+    pub fn primary(span: impl Into<Span>, message: impl Into<String>) -> Self {
+        Self::new(LabelKind::Primary, span, message)
+    }
+
+    /// Creates a [`LabelKind::Secondary`] label with the given span and message.
+    pub fn secondary(span: impl Into<Span>, message: impl Into<String>) -> Self {
+        Self::new(LabelKind::Secondary, span, message)
+    }
+}
+
+/// The kind of a [Child] diagnostic.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum ChildKind {
+    Note,
+    Help,
+    Info,
+}
+
+impl ChildKind {
+    /// Returns the name of this kind, as written before a child's message.
+    fn name(self) -> &'static str {
+        match self {
+            ChildKind::Note => "note",
+            ChildKind::Help => "help",
+            ChildKind::Info => "info",
+        }
+    }
+}
+
+/// A sub-diagnostic attached to a [Report], such as a note explaining it or help for fixing it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Child {
+    pub kind: ChildKind,
+    pub message: String,
+    pub location: Option<Span>,
+}
+
+impl Child {
+    /// Creates a new `Child` with the given kind and message.  Defaults with no [Span].
+    pub fn new(kind: ChildKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            location: None,
+        }
+    }
+
+    /// Creates a [`ChildKind::Note`] with the given message.
+    pub fn note(message: impl Into<String>) -> Self {
+        Self::new(ChildKind::Note, message)
+    }
+
+    /// Creates a [`ChildKind::Help`] with the given message.
+    pub fn help(message: impl Into<String>) -> Self {
+        Self::new(ChildKind::Help, message)
+    }
+
+    /// Creates a [`ChildKind::Info`] with the given message.
+    pub fn info(message: impl Into<String>) -> Self {
+        Self::new(ChildKind::Info, message)
+    }
+
+    /// Adds a location to this sub-diagnostic.  Accepts either a [Span] or a single [Location].
+    pub fn location(mut self, location: impl Into<Option<Span>>) -> Self {
+        self.location = location.into();
+        self
+    }
+}
+
+/// How confident a [Suggestion] is that applying it results in the intended code.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum Applicability {
+    /// The suggestion is definitely what the user intended, and can be applied automatically.
+    MachineApplicable,
+    /// The suggestion may be what the user intended, but it is uncertain.
+    MaybeIncorrect,
+    /// The suggestion contains placeholders like `(...)`, which must be filled in by the user.
+    HasPlaceholders,
+    /// The applicability of the suggestion is unknown.
+    Unspecified,
+}
+
+/// A replacement of the text covered by a span.  Empty spans insert text, and empty replacements
+/// remove text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit {
+    pub span: Span,
+    pub replacement: String,
+}
+
+impl Edit {
+    /// Creates a new `Edit` which replaces the given span with the given text.
+    pub fn new(span: impl Into<Span>, replacement: impl Into<String>) -> Self {
+        Self {
+            span: span.into(),
+            replacement: replacement.into(),
+        }
+    }
+
+    /// Creates an `Edit` which inserts the given text at a location.
+    pub fn insert(location: Location, text: impl Into<String>) -> Self {
+        Self::new(location, text)
+    }
+
+    /// Creates an `Edit` which removes the text covered by a span.
+    pub fn remove(span: Span) -> Self {
+        Self::new(span, "")
+    }
+}
+
+/// A suggested fix for a diagnostic, made up of edits to the source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    pub message: String,
+    pub applicability: Applicability,
+    pub edits: Vec<Edit>,
+}
+
+impl Suggestion {
+    /// Creates a new `Suggestion` with the given message and applicability.  Defaults with no
+    /// [Edit]s.
+    pub fn new(message: impl Into<String>, applicability: Applicability) -> Self {
+        Self {
+            message: message.into(),
+            applicability,
+            edits: Vec::new(),
+        }
+    }
+
+    /// Adds an edit to this suggestion.
+    pub fn edit(mut self, edit: Edit) -> Self {
+        self.edits.push(edit);
+        self
+    }
+}
+
 /// A diagnostic report.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Report {
-    pub location: Option<Location>,
+    pub location: Option<Span>,
     pub severity: Severity,
+    pub code: Option<String>,
     pub message: String,
+    pub labels: Vec<Label>,
+    pub children: Vec<Child>,
+    pub suggestions: Vec<Suggestion>,
 }
 
 impl Report {
-    /// Creates a new `Report` with the given severity and message.  Defaults with no [Location].
+    /// Creates a new `Report` with the given severity and message.  Defaults with no [Span].
     pub fn new(severity: Severity, message: impl Into<String>) -> Self {
         Self {
             location: None,
             severity,
+            code: None,
             message: message.into(),
+            labels: Vec::new(),
+            children: Vec::new(),
+            suggestions: Vec::new(),
         }
     }
 
@@ -205,33 +909,410 @@ impl Report {
         Self::new(Severity::Note, message)
     }
 
-    /// Adds a location to this diagnostic report.
-    pub fn location(mut self, location: impl Into<Option<Location>>) -> Self {
+    /// Adds a location to this diagnostic report.  Accepts either a [Span] or a single [Location].
+    pub fn location(mut self, location: impl Into<Option<Span>>) -> Self {
         self.location = location.into();
         self
     }
 
-    /// Creates a renderer for a single diagnostic report.
-    pub fn render<'a>(&'a self, styles: &'a Styles) -> Renderer<'a> {
-        Renderer::new(styles, std::slice::from_ref(self))
+    /// Adds a diagnostic code to this report, such as `E0412`.  Rendered next to the severity,
+    /// as in `error[E0412]: ...`.  A [Registry] can map codes to longer explanations.
+    pub fn code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
     }
-}
 
-/// The styles used to render [Report]s.
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct Styles {
-    pub location: Style,
-    pub bug: Style,
-    pub error: Style,
-    pub warning: Style,
+    /// Adds a label to this diagnostic report.
+    ///
+    /// ```
+    /// # use reporting::{error, File, Label, Span, Styles};
+    /// let file = File::new("test.txt", "let x = 1;\nlet x = 2;");
+    ///
+    /// let report = error!("`x` is defined multiple times")
+    ///     .label(Label::secondary(Span::new(file.clone(), 4..5), "first defined here"))
+    ///     .label(Label::primary(Span::new(file.clone(), 15..16), "redefined here"));
+    ///
+    /// assert_eq!(report.primary_span(), Some(&Span::new(file.clone(), 15..16)));
+    /// ```
+    ///
+    /// Labels are written to the right of their underline if they fit, and otherwise stacked
+    /// beneath it, connected to their span by a `|`.
+    ///
+    /// ```
+    /// # use reporting::{error, File, Span, Styles};
+    /// let file = File::new("test.txt", "let x = f(a, b);");
+    /// let report = error!("Mismatched argument types")
+    ///     .primary_label(Span::new(file.clone(), 8..9), "this function takes `i32`s")
+    ///     .secondary_label(Span::new(file.clone(), 10..11), "`i32`")
+    ///     .secondary_label(Span::new(file.clone(), 13..14), "`str`");
+    ///
+    /// assert_eq!(
+    ///     report.render(&Styles::plain()).to_string(),
+    ///     "error: Mismatched argument types\n\
+    ///      \x20--> test.txt:1:9\n\
+    ///      \x20 |\n\
+    ///      1 | let x = f(a, b);\n\
+    ///      \x20 |         ^ -  - `str`\n\
+    ///      \x20 |         | |\n\
+    ///      \x20 |         | `i32`\n\
+    ///      \x20 |         this function takes `i32`s\n"
+    /// );
+    /// ```
+    pub fn label(mut self, label: Label) -> Self {
+        self.labels.push(label);
+        self
+    }
+
+    /// Adds a [`LabelKind::Primary`] label to this diagnostic report.
+    pub fn primary_label(self, span: impl Into<Span>, message: impl Into<String>) -> Self {
+        self.label(Label::primary(span, message))
+    }
+
+    /// Adds a [`LabelKind::Secondary`] label to this diagnostic report.
+    pub fn secondary_label(self, span: impl Into<Span>, message: impl Into<String>) -> Self {
+        self.label(Label::secondary(span, message))
+    }
+
+    /// Attaches a sub-diagnostic to this diagnostic report.
+    ///
+    /// ```
+    /// # use reporting::{error, Child, ChildKind, File, Span};
+    /// let file = File::new("test.txt", "import stds;");
+    ///
+    /// let report = error!("Could not find package `{}`", "stds")
+    ///     .location(Span::new(file.clone(), 7..11))
+    ///     .child(Child::help("Perhaps you meant `std`?"));
+    ///
+    /// assert_eq!(report.children[0].kind, ChildKind::Help);
+    /// ```
+    ///
+    /// Children are rendered beneath the snippet as `= kind: message` lines, with the lines of
+    /// multi-line messages aligned.
+    ///
+    /// ```
+    /// # use reporting::{error, Child, File, Span, Styles};
+    /// let file = File::new("test.txt", "import stds;");
+    ///
+    /// let report = error!("Could not find package `stds`")
+    ///     .location(Span::new(file.clone(), 7..11))
+    ///     .child(Child::note("Packages are searched for in `./lib`\nand `~/.lib`"))
+    ///     .child(Child::help("Perhaps you meant `std`?"));
+    ///
+    /// assert_eq!(
+    ///     report.render(&Styles::plain()).to_string(),
+    ///     "error: Could not find package `stds`\n\
+    ///      \x20--> test.txt:1:8\n\
+    ///      \x20 |\n\
+    ///      1 | import stds;\n\
+    ///      \x20 |        ^^^^\n\
+    ///      \x20 |\n\
+    ///      \x20 = note: Packages are searched for in `./lib`\n\
+    ///      \x20         and `~/.lib`\n\
+    ///      \x20 = help: Perhaps you meant `std`?\n"
+    /// );
+    /// ```
+    pub fn child(mut self, child: Child) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Attaches a [`ChildKind::Note`] with the given message to this diagnostic report.
+    pub fn with_note(self, message: impl Into<String>) -> Self {
+        self.child(Child::note(message))
+    }
+
+    /// Attaches a [`ChildKind::Help`] with the given message to this diagnostic report.
+    pub fn with_help(self, message: impl Into<String>) -> Self {
+        self.child(Child::help(message))
+    }
+
+    /// Attaches a [`ChildKind::Info`] with the given message to this diagnostic report.
+    pub fn with_info(self, message: impl Into<String>) -> Self {
+        self.child(Child::info(message))
+    }
+
+    /// Adds a suggested fix to this diagnostic report.
+    ///
+    /// Suggestions which replace or insert text within a line are rendered as the patched line,
+    /// with the new text underlined by `~` or `+`.  Any others are rendered as the removed lines,
+    /// marked with `-`, followed by the lines replacing them, marked with `+`.
+    ///
+    /// ```
+    /// # use reporting::{error, Applicability, Edit, File, Location, Span, Styles, Suggestion};
+    /// let file = File::new("test.txt", "import stds;");
+    /// let stds = Span::new(file.clone(), 7..11);
+    ///
+    /// let report = error!("Could not find package `{}`", "stds")
+    ///     .location(stds.clone())
+    ///     .suggestion(
+    ///         Suggestion::new("A package with a similar name exists", Applicability::MaybeIncorrect)
+    ///             .edit(Edit::new(stds.clone(), "std")),
+    ///     )
+    ///     .suggestion(
+    ///         Suggestion::new("Import a module of it", Applicability::MaybeIncorrect)
+    ///             .edit(Edit::insert(Location::new(file.clone(), 11), "::io")),
+    ///     )
+    ///     .suggestion(
+    ///         Suggestion::new("Import two packages", Applicability::MaybeIncorrect)
+    ///             .edit(Edit::new(stds.clone(), "std;\nimport core")),
+    ///     );
+    ///
+    /// assert_eq!(
+    ///     report.render(&Styles::plain()).to_string(),
+    ///     "error: Could not find package `stds`\n\
+    ///      \x20--> test.txt:1:8\n\
+    ///      \x20 |\n\
+    ///      1 | import stds;\n\
+    ///      \x20 |        ^^^^\n\
+    ///      help: A package with a similar name exists\n\
+    ///      \x20 |\n\
+    ///      1 | import std;\n\
+    ///      \x20 |        ~~~\n\
+    ///      help: Import a module of it\n\
+    ///      \x20 |\n\
+    ///      1 | import stds::io;\n\
+    ///      \x20 |            ++++\n\
+    ///      help: Import two packages\n\
+    ///      \x20 |\n\
+    ///      1 - import stds;\n\
+    ///      1 + import std;\n\
+    ///      2 + import core;\n"
+    /// );
+    ///
+    /// // Lines numbered past those replaced widen the gutter.
+    /// let file = File::new("test.txt", "1\n2\n3\n4\n5\n6\n7\n8\n9");
+    /// let nine = Span::new(file.clone(), 16..17);
+    /// let report = error!("Expected a word")
+    ///     .location(nine.clone())
+    ///     .suggestion(
+    ///         Suggestion::new("Spell it out", Applicability::MaybeIncorrect)
+    ///             .edit(Edit::new(nine, "n\ni\nne")),
+    ///     );
+    ///
+    /// assert_eq!(
+    ///     report.render(&Styles::plain()).to_string(),
+    ///     "error: Expected a word\n\
+    ///      \x20 --> test.txt:9:1\n\
+    ///      \x20  |\n\
+    ///      \x209 | 9\n\
+    ///      \x20  | ^\n\
+    ///      help: Spell it out\n\
+    ///      \x20  |\n\
+    ///      \x209 - 9\n\
+    ///      \x209 + n\n\
+    ///      10 + i\n\
+    ///      11 + ne\n"
+    /// );
+    ///
+    /// // Removing a line break joins the next line onto the patched line.
+    /// let file = File::new("test.txt", "let x = 1 +\n    2;");
+    /// let report = error!("Expected an expression")
+    ///     .location(Span::new(file.clone(), 11..12))
+    ///     .suggestion(
+    ///         Suggestion::new("Join the lines", Applicability::MachineApplicable)
+    ///             .edit(Edit::new(Span::new(file.clone(), 11..16), " ")),
+    ///     );
+    ///
+    /// assert_eq!(
+    ///     report.render(&Styles::plain()).to_string(),
+    ///     "error: Expected an expression\n\
+    ///      \x20--> test.txt:1:12\n\
+    ///      \x20 |\n\
+    ///      1 | let x = 1 +\n\
+    ///      \x20 |            ^\n\
+    ///      help: Join the lines\n\
+    ///      \x20 |\n\
+    ///      1 - let x = 1 +\n\
+    ///      2 -     2;\n\
+    ///      1 + let x = 1 + 2;\n"
+    /// );
+    ///
+    /// // A trailing line break doesn't start another line.
+    /// let file = File::new("test.txt", "import std;");
+    /// let report = error!("Expected `core` to be imported")
+    ///     .location(Span::new(file.clone(), 11..11))
+    ///     .suggestion(
+    ///         Suggestion::new("Import it", Applicability::MachineApplicable)
+    ///             .edit(Edit::insert(Location::new(file.clone(), 11), "\nimport core;\n")),
+    ///     );
+    ///
+    /// assert_eq!(
+    ///     report.render(&Styles::plain()).to_string(),
+    ///     "error: Expected `core` to be imported\n\
+    ///      \x20--> test.txt:1:12\n\
+    ///      \x20 |\n\
+    ///      1 | import std;\n\
+    ///      \x20 |            ^\n\
+    ///      help: Import it\n\
+    ///      \x20 |\n\
+    ///      1 - import std;\n\
+    ///      1 + import std;\n\
+    ///      2 + import core;\n"
+    /// );
+    /// ```
+    pub fn suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
+
+    /// Returns the span this report points to: its location if it has one, otherwise the span of
+    /// its first primary label.
+    pub fn primary_span(&self) -> Option<&Span> {
+        self.location.as_ref().or_else(|| {
+            self.labels
+                .iter()
+                .find(|label| label.kind == LabelKind::Primary)
+                .or(self.labels.first())
+                .map(|label| &label.span)
+        })
+    }
+
+    /// Creates a renderer for a single diagnostic report.
+    pub fn render<'a>(&'a self, styles: &'a Styles) -> Renderer<'a> {
+        Renderer::new(styles, std::slice::from_ref(self))
+    }
+}
+
+/// When to color output.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color output written to a terminal, unless disabled by the environment.
+    #[default]
+    Auto,
+    /// Always color output.
+    Always,
+    /// Never color output.
+    Never,
+}
+
+impl ColorChoice {
+    /// Returns `true` if output written to the given stream should be colored.
+    ///
+    /// [`ColorChoice::Auto`] colors output if `CLICOLOR_FORCE` is set to anything but `0`.
+    /// Otherwise, output is only colored if the stream is a terminal, `NO_COLOR` isn't set,
+    /// `CLICOLOR` isn't `0` and `TERM` isn't `dumb`.
+    ///
+    /// ```
+    /// # use reporting::ColorChoice;
+    /// use std::env::{remove_var, set_var};
+    /// for name in ["CLICOLOR_FORCE", "NO_COLOR", "CLICOLOR", "TERM"] {
+    ///     remove_var(name);
+    /// }
+    ///
+    /// // A file isn't a terminal.
+    /// let file = std::fs::File::open(std::env::current_exe()?)?;
+    /// assert!(!ColorChoice::Auto.should_color(&file));
+    /// assert!(ColorChoice::Always.should_color(&file));
+    ///
+    /// set_var("CLICOLOR_FORCE", "1");
+    /// assert!(ColorChoice::Auto.should_color(&file));
+    /// assert!(!ColorChoice::Never.should_color(&file));
+    /// set_var("CLICOLOR_FORCE", "0");
+    /// assert!(!ColorChoice::Auto.should_color(&file));
+    /// remove_var("CLICOLOR_FORCE");
+    ///
+    /// // The controlling terminal, if there is one.
+    /// if let Ok(terminal) = std::fs::File::options().write(true).open("/dev/tty") {
+    ///     assert!(ColorChoice::Auto.should_color(&terminal));
+    ///     for (name, value) in [("NO_COLOR", "1"), ("CLICOLOR", "0"), ("TERM", "dumb")] {
+    ///         set_var(name, value);
+    ///         assert!(!ColorChoice::Auto.should_color(&terminal));
+    ///         set_var("CLICOLOR_FORCE", "1");
+    ///         assert!(ColorChoice::Auto.should_color(&terminal));
+    ///         remove_var("CLICOLOR_FORCE");
+    ///         remove_var(name);
+    ///     }
+    /// }
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn should_color(self, stream: &impl IsTerminal) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                let var = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+                if var("CLICOLOR_FORCE").is_some_and(|value| value != "0") {
+                    true
+                } else {
+                    var("NO_COLOR").is_none()
+                        && var("CLICOLOR").is_none_or(|value| value != "0")
+                        && var("TERM").is_none_or(|value| value != "dumb")
+                        && stream.is_terminal()
+                }
+            }
+        }
+    }
+}
+
+/// Returns the width in columns of the terminal a stream is written to, for use as a
+/// [`Renderer::max_width`].  Returns [None] if the stream isn't a terminal.
+///
+/// The width is taken from the `COLUMNS` environment variable if it's set, and otherwise queried
+/// from the terminal.
+///
+/// ```no_run
+/// # use reporting::{error, terminal_width, Renderer, Styles};
+/// let reports = [error!("Could not find package `stds`")];
+/// let stderr = std::io::stderr();
+/// Renderer::new(&Styles::styled(), &reports)
+///     .max_width(terminal_width(&stderr))
+///     .write_to_stream(stderr)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn terminal_width(stream: &impl IsTerminal) -> Option<usize> {
+    if !stream.is_terminal() {
+        return None;
+    }
+
+    let columns = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok());
+    columns.filter(|&columns| columns > 0).or_else(|| {
+        terminal_size::terminal_size().map(|(terminal_size::Width(width), _)| width.into())
+    })
+}
+
+/// The styles used to render [Report]s.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Styles {
+    pub location: Style,
+    pub bug: Style,
+    pub error: Style,
+    pub warning: Style,
     pub note: Style,
+    pub help: Style,
+    pub info: Style,
+    pub addition: Style,
+    pub removal: Style,
     pub colon: Style,
     pub message: Style,
     pub snippet: Style,
     pub cursor: Style,
+    pub secondary_cursor: Style,
+    pub gutter: Style,
 }
 
 impl Styles {
+    /// Creates [`Styles::styled`] if output written to the given stream should be colored, as
+    /// decided by [`ColorChoice::Auto`], otherwise [`Styles::plain`].
+    ///
+    /// ```
+    /// # use reporting::Styles;
+    /// std::env::remove_var("CLICOLOR_FORCE");
+    /// std::env::set_var("NO_COLOR", "1");
+    /// assert_eq!(Styles::auto(&std::io::stderr()), Styles::plain());
+    ///
+    /// std::env::set_var("CLICOLOR_FORCE", "1");
+    /// assert_eq!(Styles::auto(&std::io::stderr()), Styles::styled());
+    /// ```
+    pub fn auto(stream: &impl IsTerminal) -> Self {
+        if ColorChoice::Auto.should_color(stream) {
+            Self::styled()
+        } else {
+            Self::plain()
+        }
+    }
+
     /// Creates a set of [Styles] with the given styles.
     pub const fn styled() -> Self {
         Self {
@@ -240,10 +1321,16 @@ impl Styles {
             error: Style::new().fg_color(Some(Color::Ansi(AnsiColor::BrightRed))),
             warning: Style::new().fg_color(Some(Color::Ansi(AnsiColor::BrightYellow))),
             note: Style::new().fg_color(Some(Color::Ansi(AnsiColor::BrightCyan))),
+            help: Style::new().fg_color(Some(Color::Ansi(AnsiColor::BrightGreen))),
+            info: Style::new().fg_color(Some(Color::Ansi(AnsiColor::BrightBlue))),
+            addition: Style::new().fg_color(Some(Color::Ansi(AnsiColor::BrightGreen))),
+            removal: Style::new().fg_color(Some(Color::Ansi(AnsiColor::BrightRed))),
             colon: Style::new().fg_color(Some(Color::Ansi(AnsiColor::BrightBlack))),
             message: Style::new().fg_color(Some(Color::Ansi(AnsiColor::BrightWhite))),
             snippet: Style::new(),
             cursor: Style::new().fg_color(Some(Color::Ansi(AnsiColor::BrightGreen))),
+            secondary_cursor: Style::new().fg_color(Some(Color::Ansi(AnsiColor::BrightBlue))),
+            gutter: Style::new().fg_color(Some(Color::Ansi(AnsiColor::BrightBlue))),
         }
     }
 
@@ -256,10 +1343,177 @@ impl Styles {
             error: Style::new(),
             warning: Style::new(),
             note: Style::new(),
+            help: Style::new(),
+            info: Style::new(),
+            addition: Style::new(),
+            removal: Style::new(),
             colon: Style::new(),
             message: Style::new(),
             snippet: Style::new(),
             cursor: Style::new(),
+            secondary_cursor: Style::new(),
+            gutter: Style::new(),
+        }
+    }
+
+    /// Returns each style, with the name of its field.
+    fn fields(&self) -> [(&'static str, &Style); 15] {
+        [
+            ("location", &self.location),
+            ("bug", &self.bug),
+            ("error", &self.error),
+            ("warning", &self.warning),
+            ("note", &self.note),
+            ("help", &self.help),
+            ("info", &self.info),
+            ("addition", &self.addition),
+            ("removal", &self.removal),
+            ("colon", &self.colon),
+            ("message", &self.message),
+            ("snippet", &self.snippet),
+            ("cursor", &self.cursor),
+            ("secondary_cursor", &self.secondary_cursor),
+            ("gutter", &self.gutter),
+        ]
+    }
+
+    /// Returns the name of the field holding a style, if it's one of these styles.
+    fn class(&self, style: &Style) -> Option<&'static str> {
+        self.fields()
+            .into_iter()
+            .find(|(_, field)| std::ptr::eq(*field, style))
+            .map(|(name, _)| name)
+    }
+
+    /// Creates a set of [Styles] in true color, for terminals with a dark background.
+    pub const fn dark() -> Self {
+        Self {
+            location: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(220, 223, 228))))
+                .bold(),
+            bug: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(255, 85, 85))))
+                .bold(),
+            error: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(255, 107, 107))))
+                .bold(),
+            warning: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(255, 203, 107))))
+                .bold(),
+            note: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(130, 170, 255))))
+                .bold(),
+            help: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(195, 232, 141))))
+                .bold(),
+            info: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(137, 221, 255))))
+                .bold(),
+            addition: Style::new().fg_color(Some(Color::Rgb(RgbColor(195, 232, 141)))),
+            removal: Style::new().fg_color(Some(Color::Rgb(RgbColor(255, 107, 107)))),
+            colon: Style::new().fg_color(Some(Color::Rgb(RgbColor(146, 153, 166)))),
+            message: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(238, 240, 244))))
+                .bold(),
+            snippet: Style::new(),
+            cursor: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(255, 107, 107))))
+                .bold(),
+            secondary_cursor: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(130, 170, 255))))
+                .bold(),
+            gutter: Style::new().fg_color(Some(Color::Rgb(RgbColor(97, 175, 239)))),
+        }
+    }
+
+    /// Creates a set of [Styles] in true color, for terminals with a light background.
+    pub const fn light() -> Self {
+        Self {
+            location: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(56, 58, 66))))
+                .bold(),
+            bug: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(202, 18, 67))))
+                .bold(),
+            error: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(202, 18, 67))))
+                .bold(),
+            warning: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(152, 104, 1))))
+                .bold(),
+            note: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(1, 132, 188))))
+                .bold(),
+            help: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(80, 161, 79))))
+                .bold(),
+            info: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(64, 120, 242))))
+                .bold(),
+            addition: Style::new().fg_color(Some(Color::Rgb(RgbColor(80, 161, 79)))),
+            removal: Style::new().fg_color(Some(Color::Rgb(RgbColor(228, 86, 73)))),
+            colon: Style::new().fg_color(Some(Color::Rgb(RgbColor(160, 161, 167)))),
+            message: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(56, 58, 66))))
+                .bold(),
+            snippet: Style::new(),
+            cursor: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(202, 18, 67))))
+                .bold(),
+            secondary_cursor: Style::new()
+                .fg_color(Some(Color::Rgb(RgbColor(64, 120, 242))))
+                .bold(),
+            gutter: Style::new().fg_color(Some(Color::Rgb(RgbColor(64, 120, 242)))),
+        }
+    }
+
+    /// Creates a set of bold, high contrast [Styles] in the 256-color palette, for terminals with
+    /// a dark background.
+    pub const fn high_contrast() -> Self {
+        Self {
+            location: Style::new()
+                .fg_color(Some(Color::Ansi256(Ansi256Color(231))))
+                .bold(),
+            bug: Style::new()
+                .fg_color(Some(Color::Ansi256(Ansi256Color(196))))
+                .bold(),
+            error: Style::new()
+                .fg_color(Some(Color::Ansi256(Ansi256Color(196))))
+                .bold(),
+            warning: Style::new()
+                .fg_color(Some(Color::Ansi256(Ansi256Color(226))))
+                .bold(),
+            note: Style::new()
+                .fg_color(Some(Color::Ansi256(Ansi256Color(51))))
+                .bold(),
+            help: Style::new()
+                .fg_color(Some(Color::Ansi256(Ansi256Color(46))))
+                .bold(),
+            info: Style::new()
+                .fg_color(Some(Color::Ansi256(Ansi256Color(39))))
+                .bold(),
+            addition: Style::new()
+                .fg_color(Some(Color::Ansi256(Ansi256Color(46))))
+                .bold(),
+            removal: Style::new()
+                .fg_color(Some(Color::Ansi256(Ansi256Color(196))))
+                .bold(),
+            colon: Style::new()
+                .fg_color(Some(Color::Ansi256(Ansi256Color(231))))
+                .bold(),
+            message: Style::new()
+                .fg_color(Some(Color::Ansi256(Ansi256Color(231))))
+                .bold(),
+            snippet: Style::new().fg_color(Some(Color::Ansi256(Ansi256Color(231)))),
+            cursor: Style::new()
+                .fg_color(Some(Color::Ansi256(Ansi256Color(196))))
+                .bold(),
+            secondary_cursor: Style::new()
+                .fg_color(Some(Color::Ansi256(Ansi256Color(51))))
+                .bold(),
+            gutter: Style::new()
+                .fg_color(Some(Color::Ansi256(Ansi256Color(231))))
+                .bold(),
         }
     }
 }
@@ -269,86 +1523,1552 @@ impl Styles {
 pub struct Renderer<'a> {
     styles: &'a Styles,
     reports: &'a [Report],
+    context_lines: usize,
+    column_unit: ColumnUnit,
+    tab_width: usize,
+    short: bool,
+    color: ColorChoice,
+    max_width: Option<usize>,
 }
 
 impl<'a> Renderer<'a> {
     /// Creates a new [Renderer] with the given styles and reports.
     pub const fn new(styles: &'a Styles, reports: &'a [Report]) -> Self {
-        Self { styles, reports }
+        Self {
+            styles,
+            reports,
+            context_lines: 0,
+            column_unit: ColumnUnit::Char,
+            tab_width: 4,
+            short: false,
+            color: ColorChoice::Auto,
+            max_width: None,
+        }
+    }
+
+    /// Sets the number of lines of context shown before and after each annotated line.  Defaults
+    /// to `0`.
+    ///
+    /// Snippets start with the location of the primary span, or for other files, their first span.
+    /// Lines are shown beside a gutter of line numbers, and gaps between them are folded into
+    /// `...`.
+    ///
+    /// ```
+    /// # use reporting::{error, File, Renderer, Span, Styles};
+    /// let file = File::new("test.txt", "fn main() {\n    let x = 1;\n    let y = 2;\n    let z = 3;\n    x + y\n}\n");
+    /// let other = File::new("other.txt", "pub fn main() {}\n");
+    /// let reports = [error!("`main` is defined multiple times")
+    ///     .secondary_label(Span::new(other.clone(), 7..11), "first defined here")
+    ///     .primary_label(Span::new(file.clone(), 3..7), "redefined here")
+    ///     .secondary_label(Span::new(file.clone(), 61..62), "used here")];
+    ///
+    /// assert_eq!(
+    ///     Renderer::new(&Styles::plain(), &reports).context_lines(1).to_string(),
+    ///     "error: `main` is defined multiple times\n\
+    ///      \x20--> test.txt:1:4\n\
+    ///      \x20 |\n\
+    ///      1 | fn main() {\n\
+    ///      \x20 |    ^^^^ redefined here\n\
+    ///      2 |     let x = 1;\n\
+    ///      ...\n\
+    ///      4 |     let z = 3;\n\
+    ///      5 |     x + y\n\
+    ///      \x20 |     - used here\n\
+    ///      6 | }\n\
+    ///      \x20::: other.txt:1:8\n\
+    ///      \x20 |\n\
+    ///      1 | pub fn main() {}\n\
+    ///      \x20 |        ---- first defined here\n"
+    /// );
+    /// ```
+    ///
+    /// The gutter is wide enough for the last line shown.
+    ///
+    /// ```
+    /// # use reporting::{error, File, Renderer, Span, Styles};
+    /// let file = File::new("test.txt", "1\n2\n3\n4\n5\n6\n7\n8\n9\n");
+    /// let reports = [error!("Expected `}}`").location(Span::new(file.clone(), 18..18))];
+    ///
+    /// assert_eq!(
+    ///     Renderer::new(&Styles::plain(), &reports).context_lines(1).to_string(),
+    ///     "error: Expected `}`\n\
+    ///      \x20 --> test.txt:10:1\n\
+    ///      \x20  |\n\
+    ///      \x209 | 9\n\
+    ///      10 |\n\
+    ///      \x20  | ^\n"
+    /// );
+    /// ```
+    pub fn context_lines(mut self, context_lines: usize) -> Self {
+        self.context_lines = context_lines;
+        self
+    }
+
+    /// Sets the unit in which column numbers are counted in location headers.  Defaults to
+    /// [`ColumnUnit::Char`].
+    ///
+    /// Underlines are measured in display columns whatever the unit, so each grapheme is underlined
+    /// as wide as it's shown.
+    ///
+    /// ```
+    /// # use reporting::{error, File, Renderer, Span, Styles};
+    /// let file = File::new("test.txt", "let cafe\u{301} = \"👨‍👩‍👧\";");
+    /// let reports = [error!("Expected a number")
+    ///     .location(Span::new(file.clone(), 13..33))
+    ///     .secondary_label(Span::new(file.clone(), 4..10), "declared here")];
+    ///
+    /// assert_eq!(
+    ///     Renderer::new(&Styles::plain(), &reports).to_string(),
+    ///     "error: Expected a number\n\
+    ///      \x20--> test.txt:1:13\n\
+    ///      \x20 |\n\
+    ///      1 | let cafe\u{301} = \"👨\u{200d}👩\u{200d}👧\";\n\
+    ///      \x20 |     ----   ^^^^\n\
+    ///      \x20 |     |\n\
+    ///      \x20 |     declared here\n"
+    /// );
+    /// ```
+    pub fn column_unit(mut self, column_unit: ColumnUnit) -> Self {
+        self.column_unit = column_unit;
+        self
+    }
+
+    /// Sets the number of columns between tab stops.  Tabs in snippets are expanded to spaces, up
+    /// to the next tab stop.  Defaults to `4`.
+    ///
+    /// ```
+    /// # use reporting::{error, File, Renderer, Span, Styles};
+    /// let file = File::new("test.txt", "if x {\n\tlet\ty = 1;\n}");
+    /// let reports = [error!("Unused variable").location(Span::new(file.clone(), 12..13))];
+    ///
+    /// assert_eq!(
+    ///     Renderer::new(&Styles::plain(), &reports).to_string(),
+    ///     "error: Unused variable\n\
+    ///      \x20--> test.txt:2:6\n\
+    ///      \x20 |\n\
+    ///      2 |     let y = 1;\n\
+    ///      \x20 |         ^\n"
+    /// );
+    /// assert_eq!(
+    ///     Renderer::new(&Styles::plain(), &reports).tab_width(8).to_string(),
+    ///     "error: Unused variable\n\
+    ///      \x20--> test.txt:2:6\n\
+    ///      \x20 |\n\
+    ///      2 |         let     y = 1;\n\
+    ///      \x20 |                 ^\n"
+    /// );
+    /// ```
+    pub fn tab_width(mut self, tab_width: usize) -> Self {
+        self.tab_width = tab_width;
+        self
+    }
+
+    /// Sets whether each report is rendered on a single line, in the style of gcc and clang.
+    /// Defaults to `false`.
+    ///
+    /// Short reports are written as `path:line:col: error: message`, without a snippet, children
+    /// or suggestions, and can be read by Vim's default `errorformat` and Emacs'
+    /// `compilation-mode`.  Vim counts columns in bytes, so set [`ColumnUnit::Byte`] for it.
+    ///
+    /// ```
+    /// # use reporting::{error, File, Renderer, Span, Styles};
+    /// let file = File::new("src/main.txt", "import stds;");
+    /// let reports = [
+    ///     error!("Could not find package `stds`")
+    ///         .code("E0412")
+    ///         .location(Span::new(file.clone(), 7..11))
+    ///         .with_help("Perhaps you meant `std`?"),
+    ///     error!("Aborting due to previous error"),
+    /// ];
+    ///
+    /// assert_eq!(
+    ///     Renderer::new(&Styles::plain(), &reports).short(true).to_string(),
+    ///     "src/main.txt:1:8: error[E0412]: Could not find package `stds`\n\
+    ///      error: Aborting due to previous error\n"
+    /// );
+    /// ```
+    pub fn short(mut self, short: bool) -> Self {
+        self.short = short;
+        self
+    }
+
+    /// Sets the maximum width of the output in columns, such as the width of the terminal given by
+    /// [terminal_width].  Defaults to none, in which case lines are never cut or wrapped.
+    ///
+    /// Source lines which don't fit are trimmed to a window around their annotations, with `...`
+    /// marking where they're cut.  Messages which don't fit are wrapped at spaces, with their
+    /// continuation lines aligned with the start of the message.  Labels, locations and short
+    /// reports are left as they are.
+    ///
+    /// ```
+    /// # use reporting::{error, File, Renderer, Span, Styles};
+    /// let source = format!("let x = [{}];", "1, ".repeat(30) + "stds");
+    /// let file = File::new("test.txt", source);
+    /// let reports = [error!("Could not find value `stds` in this scope, or in any other scope")
+    ///     .location(Span::new(file.clone(), 99..103))];
+    ///
+    /// assert_eq!(
+    ///     Renderer::new(&Styles::plain(), &reports).max_width(40).to_string(),
+    ///     "error: Could not find value `stds` in\n\
+    ///      \x20      this scope, or in any other scope\n\
+    ///      \x20--> test.txt:1:100\n\
+    ///      \x20 |\n\
+    ///      1 | ...1, 1, 1, 1, 1, 1, 1, 1, 1, stds];\n\
+    ///      \x20 |                               ^^^^\n"
+    /// );
+    /// ```
+    pub fn max_width(mut self, max_width: impl Into<Option<usize>>) -> Self {
+        self.max_width = max_width.into();
+        self
+    }
+
+    /// Creates a renderer for the reports as HTML, with the same layout and settings as this
+    /// renderer.
+    pub fn html(self) -> HtmlRenderer<'a> {
+        HtmlRenderer::new(self)
+    }
+
+    /// Creates a renderer for the reports as an SVG image, with the same layout and settings as
+    /// this renderer.
+    pub fn svg(self) -> SvgRenderer<'a> {
+        SvgRenderer::new(self)
+    }
+
+    /// Renders the reports into a writer, such as a file or a pipe, with the renderer's styles.
+    ///
+    /// ```
+    /// # use reporting::{error, File, Renderer, Span, Styles};
+    /// let file = File::new("test.txt", "import stds;");
+    /// let reports = [error!("Could not find package `stds`")
+    ///     .location(Span::new(file.clone(), 7..11))
+    ///     .with_help("Perhaps you meant `std`?")];
+    ///
+    /// let mut output = Vec::new();
+    /// Renderer::new(&Styles::plain(), &reports).write_to(&mut output)?;
+    /// assert!(output.starts_with(b"error: Could not find package `stds`\n"));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn write_to(&self, mut writer: impl Write) -> std::io::Result<()> {
+        // The output is written at once, as streams such as stderr are unbuffered.
+        writer.write_all(self.to_string().as_bytes())?;
+        writer.flush()
+    }
+
+    /// Sets when the renderer's styles are used by [`Renderer::write_to_stream`].  Defaults to
+    /// [`ColorChoice::Auto`].
+    pub fn color(mut self, color: ColorChoice) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Renders the reports into a stream, such as stderr or a file.  The renderer's styles are
+    /// only used if the renderer's [ColorChoice] colors the stream, otherwise the reports are
+    /// written as plain text.
+    ///
+    /// ```no_run
+    /// # use reporting::{error, Renderer, Styles};
+    /// let reports = [error!("Could not find package `stds`")];
+    /// Renderer::new(&Styles::styled(), &reports).write_to_stream(std::io::stderr())?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn write_to_stream(&self, stream: impl Write + IsTerminal) -> std::io::Result<()> {
+        if self.color.should_color(&stream) {
+            self.write_to(stream)
+        } else {
+            static PLAIN: Styles = Styles::plain();
+            Renderer {
+                styles: &PLAIN,
+                ..self.clone()
+            }
+            .write_to(stream)
+        }
+    }
+}
+
+impl<'a> Renderer<'a> {
+    /// Collects every span annotated by a report.  A location which isn't already labeled is
+    /// underlined without a message.
+    fn annotations(report: &Report) -> Vec<(LabelKind, &Span, Option<&str>)> {
+        let mut annotations = Vec::new();
+        if let Some(location) = &report.location {
+            if !report.labels.iter().any(|label| label.span == *location) {
+                annotations.push((LabelKind::Primary, location, None));
+            }
+        }
+        annotations.extend(report.labels.iter().map(|label| {
+            let message = Some(label.message.as_str()).filter(|message| !message.is_empty());
+            (label.kind, &label.span, message)
+        }));
+        annotations
+    }
+
+    /// Returns the width of a gutter large enough for the largest line number shown in snippets
+    /// of the given spans.
+    fn gutter_width<'s>(&self, spans: impl IntoIterator<Item = &'s Span>) -> usize {
+        spans
+            .into_iter()
+            .map(|span| {
+                let end_line = span
+                    .file
+                    .line_number(span.end.saturating_sub(1).max(span.start));
+                // Context stops at the last line with content, but a span may end after it.
+                (end_line + self.context_lines)
+                    .min(span.file.last_line())
+                    .max(end_line)
+            })
+            .max()
+            .unwrap_or(1)
+            .to_string()
+            .len()
+    }
+
+    /// Returns the largest line number of the lines shown for a suggestion once it's applied.
+    fn last_patched_line(suggestion: &Suggestion) -> usize {
+        let mut files = Vec::<&Arc<File>>::new();
+        for edit in &suggestion.edits {
+            if !files.contains(&&edit.span.file) {
+                files.push(&edit.span.file);
+            }
+        }
+
+        files
+            .into_iter()
+            .map(|file| {
+                let edits = sorted_edits(
+                    suggestion
+                        .edits
+                        .iter()
+                        .filter(|edit| edit.span.file == *file),
+                );
+                let last_line = edits
+                    .iter()
+                    .map(|edit| last_edited_line(file, edit))
+                    .max()
+                    .unwrap_or(1);
+                let added = edits
+                    .iter()
+                    .map(|edit| line_breaks(&edit.replacement))
+                    .sum::<usize>();
+                let removed = edits
+                    .iter()
+                    .map(|edit| line_breaks(&file.source()[edit.span.range()]))
+                    .sum::<usize>();
+                (last_line + added).saturating_sub(removed)
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Writes the source lines of the given annotations, with their underlines and labels beneath.
+    fn fmt_snippet(
+        &self,
+        f: &mut Output<'_, '_>,
+        annotations: &[(LabelKind, &Span, Option<&str>)],
+        primary: &Span,
+        gutter: usize,
+    ) -> std::fmt::Result {
+        // Snippets are grouped by file, starting with the file of the primary span.
+        let mut files = vec![&primary.file];
+        for (_, span, _) in annotations {
+            if !files.contains(&&span.file) {
+                files.push(&span.file);
+            }
+        }
+
+        for file in files {
+            let annotations = annotations
+                .iter()
+                .filter(|(_, span, _)| span.file == *file)
+                .collect::<Vec<_>>();
+
+            // The primary span's file is introduced with `-->`, any others with `:::`.
+            let (arrow, location) = if file == &primary.file {
+                ("-->", primary)
+            } else {
+                (":::", annotations[0].1)
+            };
+            self.fmt_location(f, arrow, location, gutter)?;
+            self.fmt_gutter_line(f, gutter)?;
+
+            self.fmt_file(f, file, &annotations, gutter)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the annotated lines of a single file, prefixed by a gutter of line numbers.  Spans
+    /// covering multiple lines are drawn in full, connected by a bar in the left margin.
+    fn fmt_file(
+        &self,
+        f: &mut Output<'_, '_>,
+        file: &File,
+        annotations: &[&(LabelKind, &Span, Option<&str>)],
+        gutter: usize,
+    ) -> std::fmt::Result {
+        // Lay out each annotation on its lines, keyed by line number.
+        let mut lines = BTreeMap::<usize, Vec<LineAnnotation>>::new();
+        let mut multiline = Vec::new();
+        for &&(kind, span, message) in annotations {
+            let start_line = file.line_number(span.start);
+            let end_line = file.line_number(span.end.saturating_sub(1).max(span.start));
+            let start = file.line_range(start_line).unwrap();
+            let end = file.line_range(end_line).unwrap();
+
+            if start_line == end_line {
+                let (start_column, end_column) = display_columns(
+                    &file.source[start.clone()],
+                    span.start.saturating_sub(start.start),
+                    span.end.saturating_sub(start.start),
+                    self.tab_width,
+                );
+                lines.entry(start_line).or_default().push(LineAnnotation {
+                    kind,
+                    start: start_column,
+                    end: end_column,
+                    message,
+                });
+                continue;
+            }
+
+            multiline.push(MultilineAnnotation {
+                kind,
+                message,
+                depth: 0,
+                start_line,
+                end_line,
+                start: display_column(
+                    &file.source[start.clone()],
+                    span.start.saturating_sub(start.start),
+                    self.tab_width,
+                ),
+                end: display_column(
+                    &file.source[end.clone()],
+                    (span.end - 1).saturating_sub(end.start),
+                    self.tab_width,
+                ),
+                // Spans starting at the beginning of a line's code are drawn with a `/`.
+                slash: file
+                    .source
+                    .get(start.start..span.start)
+                    .is_some_and(|code| code.trim().is_empty()),
+            });
+            for line in start_line..=end_line {
+                lines.entry(line).or_default();
+            }
+        }
+
+        // Surround the annotated lines with context.
+        let last_line = file.last_line();
+        for line in lines.keys().copied().collect::<Vec<_>>() {
+            let context = line.saturating_sub(self.context_lines).max(1)
+                ..=(line + self.context_lines).min(last_line);
+            for line in context {
+                lines.entry(line).or_default();
+            }
+        }
+
+        // Outer spans are given the leftmost bars.
+        multiline.sort_by_key(|annotation| {
+            (
+                annotation.start_line,
+                annotation.start,
+                Reverse(annotation.end_line),
+            )
+        });
+        for (depth, annotation) in multiline.iter_mut().enumerate() {
+            annotation.depth = depth;
+        }
+
+        // Code is written after the gutter and the bars of multiline spans.
+        let indent = gutter + 3;
+        let margin = if multiline.is_empty() {
+            indent
+        } else {
+            indent + multiline.len() + 1
+        };
+
+        // Long lines are trimmed to a window around the annotations, which are shifted into it.
+        let line_width = lines
+            .keys()
+            .map(|&line| display_width(file.line(line).unwrap(), self.tab_width))
+            .max()
+            .unwrap_or(0);
+        let annotated = |kind: Option<LabelKind>| {
+            lines
+                .values()
+                .flatten()
+                .filter(|annotation| kind.is_none_or(|kind| annotation.kind == kind))
+                .map(|annotation| annotation.start..annotation.end)
+                .chain(
+                    multiline
+                        .iter()
+                        .filter(|annotation| kind.is_none_or(|kind| annotation.kind == kind))
+                        .flat_map(|annotation| {
+                            [
+                                annotation.start..annotation.start + 1,
+                                annotation.end..annotation.end + 1,
+                            ]
+                        }),
+                )
+                .reduce(|a, b| a.start.min(b.start)..a.end.max(b.end))
+        };
+        // Primary annotations are kept in view in preference to secondary ones.
+        let annotated = annotated(Some(LabelKind::Primary))
+            .or_else(|| annotated(None))
+            .unwrap_or(0..0);
+        let window = self.window(margin, line_width, annotated);
+        for annotation in lines.values_mut().flatten() {
+            (annotation.start, annotation.end) = window.columns(annotation.start, annotation.end);
+        }
+        for annotation in &mut multiline {
+            annotation.start = window.column(annotation.start);
+            annotation.end = window.column(annotation.end);
+        }
+
+        let mut previous_line = None;
+        for (&line_number, annotations) in &lines {
+            // Fold the gap between lines which aren't adjacent.
+            if previous_line.is_some_and(|previous| previous + 1 < line_number) {
+                f.paint(&self.styles.gutter, "...")?;
+                writeln!(f)?;
+            }
+            previous_line = Some(line_number);
+
+            // Write the line of code, with the bars of the spans covering it.
+            let mut canvas = Canvas::default();
+            canvas.put_str(
+                0,
+                0,
+                &format!("{line_number:>gutter$} |"),
+                &self.styles.gutter,
+            );
+            for annotation in &multiline {
+                let style = self.label_style(annotation.kind);
+                if annotation.start_line == line_number && annotation.slash {
+                    canvas.put(0, indent + annotation.depth, '/', style);
+                } else if annotation.start_line < line_number && line_number <= annotation.end_line
+                {
+                    canvas.put(0, indent + annotation.depth, '|', style);
+                }
+            }
+            self.draw_highlighted(
+                &mut canvas,
+                margin,
+                file.line(line_number).unwrap(),
+                &[],
+                &self.styles.snippet,
+                window,
+            );
+            canvas.fmt(f)?;
+
+            // Draw the underlines, followed by the start and end markers of multiline spans.
+            let mut canvas = Canvas::default();
+            self.draw_annotations(&mut canvas, margin, annotations);
+
+            let mut marker_rows = vec![None; multiline.len()];
+            for annotation in &multiline {
+                if annotation.start_line == line_number && !annotation.slash {
+                    let row = canvas.rows.len();
+                    let style = self.label_style(annotation.kind);
+                    for column in indent + annotation.depth + 1..margin + annotation.start {
+                        canvas.put(row, column, '_', style);
+                    }
+                    canvas.put(
+                        row,
+                        margin + annotation.start,
+                        annotation.kind.underline(),
+                        style,
+                    );
+                    marker_rows[annotation.depth] = Some(row);
+                }
+            }
+            for annotation in multiline.iter().rev() {
+                if annotation.end_line == line_number {
+                    let row = canvas.rows.len();
+                    let style = self.label_style(annotation.kind);
+                    canvas.put(row, indent + annotation.depth, '|', style);
+                    for column in indent + annotation.depth + 1..margin + annotation.end {
+                        canvas.put(row, column, '_', style);
+                    }
+                    canvas.put(
+                        row,
+                        margin + annotation.end,
+                        annotation.kind.underline(),
+                        style,
+                    );
+                    if let Some(message) = annotation.message {
+                        canvas.put_lines(row, margin + annotation.end + 2, message, style);
+                    }
+                    marker_rows[annotation.depth] = Some(row);
+                }
+            }
+
+            // Fill in the bars of the spans which continue past their markers.
+            for annotation in &multiline {
+                let rows = if annotation.start_line == line_number && !annotation.slash {
+                    marker_rows[annotation.depth].unwrap() + 1..canvas.rows.len()
+                } else if annotation.end_line == line_number {
+                    0..marker_rows[annotation.depth].unwrap()
+                } else if annotation.start_line <= line_number && line_number < annotation.end_line
+                {
+                    0..canvas.rows.len()
+                } else {
+                    continue;
+                };
+
+                for row in rows {
+                    if canvas.is_blank(row, indent + annotation.depth) {
+                        let style = self.label_style(annotation.kind);
+                        canvas.put(row, indent + annotation.depth, '|', style);
+                    }
+                }
+            }
+
+            for row in 0..canvas.rows.len() {
+                canvas.put(row, gutter + 1, '|', &self.styles.gutter);
+            }
+            canvas.fmt(f)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes a line introducing a snippet of a file, such as `--> path:line:col`.
+    fn fmt_location(
+        &self,
+        f: &mut Output<'_, '_>,
+        arrow: &str,
+        span: &Span,
+        gutter: usize,
+    ) -> std::fmt::Result {
+        write!(f, "{:gutter$}", "")?;
+        f.paint(&self.styles.gutter, arrow)?;
+        write!(f, " ")?;
+        f.paint(
+            &self.styles.location,
+            span.start_location().display(self.column_unit),
+        )?;
+        writeln!(f)
+    }
+
+    /// Writes an empty line of the gutter.
+    fn fmt_gutter_line(&self, f: &mut Output<'_, '_>, gutter: usize) -> std::fmt::Result {
+        write!(f, "{:gutter$} ", "")?;
+        f.paint(&self.styles.gutter, '|')?;
+        writeln!(f)
+    }
+
+    /// Writes the title of a report or one of its parts, such as `note: message`.
+    fn fmt_title(
+        &self,
+        f: &mut Output<'_, '_>,
+        name: &str,
+        style: &Style,
+        message: &str,
+    ) -> std::fmt::Result {
+        f.paint(style, name)?;
+        f.paint(&self.styles.colon, ':')?;
+        write!(f, " ")?;
+        if self.max_width.is_some() && !self.short {
+            self.fmt_message(f, message, name.width() + 2)?;
+        } else {
+            f.paint(&self.styles.message, message)?;
+        }
+        writeln!(f)
+    }
+
+    /// Writes a message beneath the gutter, such as `= note: message`.
+    fn fmt_footer(
+        &self,
+        f: &mut Output<'_, '_>,
+        name: &str,
+        style: &Style,
+        message: &str,
+        gutter: usize,
+    ) -> std::fmt::Result {
+        write!(f, "{:gutter$} ", "")?;
+        f.paint(&self.styles.gutter, '=')?;
+        write!(f, " ")?;
+        f.paint(style, name)?;
+        f.paint(&self.styles.colon, ':')?;
+        write!(f, " ")?;
+
+        // Continuation lines are aligned with the start of the message.
+        self.fmt_message(f, message, gutter + 3 + name.len() + 2)?;
+        writeln!(f)
+    }
+
+    /// Writes a message starting at the given column, with its continuation lines indented to
+    /// it.  Lines which don't fit within the maximum width are wrapped.
+    fn fmt_message(
+        &self,
+        f: &mut Output<'_, '_>,
+        message: &str,
+        indent: usize,
+    ) -> std::fmt::Result {
+        // However narrow the maximum width, a few words fit on each line.
+        let width = self.max_width.map_or(usize::MAX, |max_width| {
+            max_width.saturating_sub(indent).max(20)
+        });
+
+        f.open(&self.styles.message)?;
+        let lines = message.lines().flat_map(|line| wrap(line, width));
+        for (idx, line) in lines.enumerate() {
+            if idx > 0 {
+                write!(f, "\n{:indent$}", "")?;
+            }
+            write!(f, "{line}")?;
+        }
+        f.close(&self.styles.message)
+    }
+
+    /// Writes a suggestion, showing the lines it changes.  Edits which replace or insert text
+    /// within a line are shown in the patched line, underlined with `~` or `+`.  Otherwise, the
+    /// original lines are shown marked with `-`, followed by the patched lines marked with `+`.
+    fn fmt_suggestion(
+        &self,
+        f: &mut Output<'_, '_>,
+        suggestion: &Suggestion,
+        primary: Option<&Span>,
+        gutter: usize,
+    ) -> std::fmt::Result {
+        self.fmt_title(f, "help", &self.styles.help, &suggestion.message)?;
+
+        let mut files = Vec::<&Arc<File>>::new();
+        for edit in &suggestion.edits {
+            if !files.contains(&&edit.span.file) {
+                files.push(&edit.span.file);
+            }
+        }
+
+        for file in files {
+            let edits = sorted_edits(
+                suggestion
+                    .edits
+                    .iter()
+                    .filter(|edit| edit.span.file == *file),
+            );
+            if edits.is_empty() {
+                continue;
+            }
+            if primary.is_none_or(|primary| primary.file != *file) {
+                self.fmt_location(f, ":::", &edits[0].span, gutter)?;
+            }
+            self.fmt_gutter_line(f, gutter)?;
+
+            let first_line = file.line_number(edits[0].span.start);
+            let last_end = edits.iter().map(|edit| edit.span.end).max().unwrap();
+            let last_line = edits
+                .iter()
+                .map(|edit| last_edited_line(file, edit))
+                .max()
+                .unwrap();
+
+            let inline = edits.iter().all(|edit| {
+                let line = file.line_number(edit.span.start);
+                !edit.replacement.is_empty()
+                    && !edit.replacement.contains(['\r', '\n'])
+                    && edit.span.end <= file.line_range(line).unwrap().end
+            });
+
+            if inline {
+                for line_number in first_line..=last_line {
+                    let range = file.line_range(line_number).unwrap();
+                    let line_edits = edits
+                        .iter()
+                        .copied()
+                        .filter(|edit| {
+                            range.contains(&edit.span.start) || range.end == edit.span.start
+                        })
+                        .collect::<Vec<_>>();
+                    if line_edits.is_empty() {
+                        continue;
+                    }
+
+                    let (patched, inserted) = apply_edits(file.source(), range, &line_edits);
+                    let window =
+                        self.highlighted_window(gutter + 3, [(patched.as_str(), &*inserted)]);
+                    let mut canvas = Canvas::default();
+                    canvas.put_str(
+                        0,
+                        0,
+                        &format!("{line_number:>gutter$} |"),
+                        &self.styles.gutter,
+                    );
+                    self.draw_highlighted(
+                        &mut canvas,
+                        gutter + 3,
+                        &patched,
+                        &[],
+                        &self.styles.snippet,
+                        window,
+                    );
+                    canvas.fmt(f)?;
+
+                    let mut canvas = Canvas::default();
+                    canvas.put(0, gutter + 1, '|', &self.styles.gutter);
+                    for (edit, range) in line_edits.iter().zip(inserted) {
+                        let char = if edit.span.is_empty() { '+' } else { '~' };
+                        let (start, end) =
+                            display_columns(&patched, range.start, range.end, self.tab_width);
+                        let (start, end) = window.columns(start, end);
+                        for column in start..end {
+                            canvas.put(0, gutter + 3 + column, char, &self.styles.addition);
+                        }
+                    }
+                    canvas.fmt(f)?;
+                }
+                continue;
+            }
+
+            // Show the removed lines, followed by the lines replacing them.
+            let removed = (first_line..=last_line)
+                .map(|line_number| {
+                    let range = file.line_range(line_number).unwrap();
+                    let removed = edits
+                        .iter()
+                        .map(|edit| {
+                            edit.span.start.max(range.start) - range.start
+                                ..edit.span.end.min(range.end).saturating_sub(range.start)
+                        })
+                        .collect::<Vec<_>>();
+                    (line_number, &file.source()[range], removed)
+                })
+                .collect::<Vec<_>>();
+
+            let region_start = file
+                .line_range(first_line)
+                .unwrap()
+                .start
+                .min(edits[0].span.start);
+            let region_end = file.line_range(last_line).unwrap().end.max(last_end);
+            let (patched, inserted) = apply_edits(file.source(), region_start..region_end, &edits);
+            let patched_file = File::new(file.path(), patched);
+            // A trailing line break doesn't start another line, unless the region is followed by
+            // the rest of its last line.
+            let mut line_count = patched_file.line_count();
+            if (region_end > file.line_range(last_line).unwrap().end
+                || region_end == file.source().len())
+                && (patched_file.source().is_empty()
+                    || patched_file.source().ends_with(['\r', '\n']))
+            {
+                line_count -= 1;
+            }
+            let added = (1..=line_count)
+                .map(|line| {
+                    let range = patched_file.line_range(line).unwrap();
+                    let added = inserted
+                        .iter()
+                        .map(|inserted| {
+                            inserted.start.max(range.start) - range.start
+                                ..inserted.end.min(range.end).saturating_sub(range.start)
+                        })
+                        .collect::<Vec<_>>();
+                    (first_line + line - 1, &patched_file.source()[range], added)
+                })
+                .collect::<Vec<_>>();
+
+            // Both the removed and added lines are trimmed to the same window, so they line up.
+            let window = self.highlighted_window(
+                gutter + 3,
+                removed
+                    .iter()
+                    .chain(&added)
+                    .map(|(_, line, highlights)| (*line, highlights.as_slice())),
+            );
+            let rows = removed
+                .iter()
+                .map(|row| (row, '-', &self.styles.removal))
+                .chain(added.iter().map(|row| (row, '+', &self.styles.addition)));
+            for ((line_number, line, highlights), marker, style) in rows {
+                let mut canvas = Canvas::default();
+                canvas.put_str(
+                    0,
+                    0,
+                    &format!("{line_number:>gutter$}"),
+                    &self.styles.gutter,
+                );
+                canvas.put(0, gutter + 1, marker, style);
+                self.draw_highlighted(&mut canvas, gutter + 3, line, highlights, style, window);
+                canvas.fmt(f)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the window in which lines up to the given width are shown after a margin, keeping
+    /// the given range of annotated display columns in view if they fit.
+    fn window(&self, margin: usize, line_width: usize, annotated: Range<usize>) -> Window {
+        // However narrow the maximum width, a little of each line is shown.
+        let Some(width) = self
+            .max_width
+            .map(|max_width| max_width.saturating_sub(margin).max(20))
+        else {
+            return Window::FULL;
+        };
+        if line_width <= width {
+            return Window::FULL;
+        }
+
+        // Lines are cut at the end if the annotations fit before the `...`, and otherwise the
+        // annotations are centered, without cutting off their start or scrolling past the end of
+        // the line.
+        let start = if annotated.end + 3 <= width {
+            0
+        } else {
+            ((annotated.start + annotated.end) / 2)
+                .saturating_sub(width / 2)
+                .min(annotated.start.saturating_sub(3))
+                .min(line_width - width)
+        };
+        Window {
+            start,
+            end: start + width,
+        }
+    }
+
+    /// Returns the window in which lines are shown after a margin, keeping the given byte ranges
+    /// of each line in view if they fit.
+    fn highlighted_window<'l>(
+        &self,
+        margin: usize,
+        lines: impl IntoIterator<Item = (&'l str, &'l [Range<usize>])>,
+    ) -> Window {
+        let mut line_width = 0;
+        let mut annotated = None::<Range<usize>>;
+        for (line, highlights) in lines {
+            line_width = line_width.max(display_width(line, self.tab_width));
+            for range in highlights.iter().filter(|range| range.start < range.end) {
+                let (start, end) = display_columns(line, range.start, range.end, self.tab_width);
+                annotated = Some(match annotated {
+                    Some(annotated) => annotated.start.min(start)..annotated.end.max(end),
+                    None => start..end,
+                });
+            }
+        }
+        self.window(margin, line_width, annotated.unwrap_or(0..0))
+    }
+
+    /// Draws a line of code starting at the given column, with tabs expanded and the given byte
+    /// ranges highlighted.  The line is trimmed to the given window, with `...` marking where it's
+    /// cut.
+    fn draw_highlighted(
+        &self,
+        canvas: &mut Canvas<'a>,
+        column: usize,
+        line: &str,
+        highlights: &[Range<usize>],
+        highlight: &'a Style,
+        window: Window,
+    ) {
+        let line_width = display_width(line, self.tab_width);
+        let cut_start = window.start > 0 && line_width > 0;
+        let cut_end = line_width > window.end;
+        let visible = if cut_start { window.start + 3 } else { 0 }..if cut_end {
+            window.end - 3
+        } else {
+            usize::MAX
+        };
+
+        // Canvas cells hold single characters, so graphemes are placed one after another rather
+        // than by display column.
+        let mut column = column;
+        if cut_start {
+            canvas.put_str(0, column, "...", &self.styles.gutter);
+            column += 3;
+        }
+        for (idx, grapheme, start, width) in graphemes(line, self.tab_width) {
+            // Graphemes partly cut off by the window are replaced with spaces.
+            if start < visible.start || start + width > visible.end {
+                let shown = (start + width)
+                    .min(visible.end)
+                    .saturating_sub(start.max(visible.start));
+                canvas.put_str(0, column, &" ".repeat(shown), &self.styles.snippet);
+                column += shown;
+                continue;
+            }
+
+            let style = if highlights.iter().any(|range| range.contains(&idx)) {
+                highlight
+            } else {
+                &self.styles.snippet
+            };
+
+            if grapheme == "\t" {
+                canvas.put_str(0, column, &" ".repeat(width), style);
+                column += width;
+            } else {
+                canvas.put_str(0, column, grapheme, style);
+                column += grapheme.chars().count();
+            }
+        }
+        if cut_end {
+            canvas.put_str(0, column, "...", &self.styles.gutter);
+        }
+    }
+
+    /// Returns the style used to draw labels of the given kind.
+    fn label_style(&self, kind: LabelKind) -> &'a Style {
+        match kind {
+            LabelKind::Primary => &self.styles.cursor,
+            LabelKind::Secondary => &self.styles.secondary_cursor,
+        }
+    }
+
+    /// Draws the underlines and labels of the annotations on a single line.  Labels which don't
+    /// fit to the right of the underlines are stacked beneath them, connected by a `|`.
+    fn draw_annotations(
+        &self,
+        canvas: &mut Canvas<'a>,
+        margin: usize,
+        annotations: &[LineAnnotation],
+    ) {
+        // Primary underlines are drawn last, so they take precedence where spans overlap.
+        let mut underlines = annotations.iter().collect::<Vec<_>>();
+        underlines.sort_by_key(|annotation| annotation.kind == LabelKind::Primary);
+        for annotation in underlines {
+            for column in annotation.start..annotation.end {
+                canvas.put(
+                    0,
+                    margin + column,
+                    annotation.kind.underline(),
+                    self.label_style(annotation.kind),
+                );
+            }
+        }
+
+        // Labels are placed from right to left.  The rightmost label is written inline if no
+        // underline extends past it.
+        let mut labeled = annotations
+            .iter()
+            .filter_map(|annotation| Some((annotation, annotation.message?)))
+            .collect::<Vec<_>>();
+        labeled.sort_by_key(|(annotation, _)| Reverse((annotation.start, annotation.end)));
+
+        // Labels of multiple lines take a row per line, with the lines of stacked labels beneath
+        // each other.
+        let max_end = annotations.iter().map(|annotation| annotation.end).max();
+        let mut row = 2;
+        for (idx, (annotation, message)) in labeled.into_iter().enumerate() {
+            let style = self.label_style(annotation.kind);
+            if idx == 0 && Some(annotation.end) == max_end {
+                let rows = canvas.put_lines(0, margin + annotation.end + 1, message, style);
+                row = row.max(rows + 1);
+                continue;
+            }
+
+            for connector_row in 1..row {
+                if canvas.is_blank(connector_row, margin + annotation.start) {
+                    canvas.put(connector_row, margin + annotation.start, '|', style);
+                }
+            }
+            row += canvas.put_lines(row, margin + annotation.start, message, style);
+        }
     }
 }
 
 impl<'a> std::fmt::Display for Renderer<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_reports(&mut Output::ansi(f))
+    }
+}
+
+impl<'a> Renderer<'a> {
+    /// Writes every report.
+    fn fmt_reports(&self, f: &mut Output<'_, '_>) -> std::fmt::Result {
         for report in self.reports {
-            // Print location information, if any.
-            let line_column = if let Some(location) = &report.location {
-                let (line, column) = location.file().line_column(location.offset()).unwrap();
-                write!(
-                    f,
-                    "{}{}:{}:{}:{} ",
+            let primary = report.primary_span();
+
+            // In short mode, reports start with their location, as in `path:line:col: error: ...`.
+            if let Some(span) = primary.filter(|_| self.short) {
+                f.paint(
                     &self.styles.location,
-                    location.file.path(),
-                    line,
-                    column,
-                    Reset
+                    span.start_location().display(self.column_unit),
                 )?;
-                Some((location, line, column))
-            } else {
-                None
+                f.paint(&self.styles.colon, ':')?;
+                write!(f, " ")?;
+            }
+
+            // Print severity label.
+            let style = match report.severity {
+                Severity::Bug => &self.styles.bug,
+                Severity::Error => &self.styles.error,
+                Severity::Warning => &self.styles.warning,
+                Severity::Note => &self.styles.note,
+            };
+            let name = match &report.code {
+                Some(code) => Cow::Owned(format!("{}[{code}]", report.severity.name())),
+                None => Cow::Borrowed(report.severity.name()),
             };
 
-            write!(f, "{}", Reset)?;
+            // Print message.  In short mode, the message is joined onto a single line
+            // and nothing else is printed.
+            let message = if self.short && report.message.contains(['\r', '\n']) {
+                Cow::Owned(
+                    report
+                        .message
+                        .split(['\r', '\n'])
+                        .filter(|line| !line.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                )
+            } else {
+                Cow::Borrowed(report.message.as_str())
+            };
+            self.fmt_title(f, &name, style, &message)?;
+            if self.short {
+                continue;
+            }
 
-            // Print severity label.
-            match report.severity {
-                Severity::Bug => write!(f, "{}bug", &self.styles.bug)?,
-                Severity::Error => write!(f, "{}error", &self.styles.error)?,
-                Severity::Warning => write!(f, "{}warning", &self.styles.warning)?,
-                Severity::Note => write!(f, "{}note", &self.styles.note)?,
+            // Print snippet, if applicable.
+            let annotations = Self::annotations(report);
+            let gutter = self.gutter_width(
+                annotations.iter().map(|(_, span, _)| *span).chain(
+                    report
+                        .children
+                        .iter()
+                        .filter_map(|child| child.location.as_ref())
+                        .chain(
+                            report
+                                .suggestions
+                                .iter()
+                                .flat_map(|suggestion| &suggestion.edits)
+                                .map(|edit| &edit.span),
+                        ),
+                ),
+            );
+            // Suggestions may add lines, which are numbered on from the lines they replace.
+            let gutter = report
+                .suggestions
+                .iter()
+                .map(Self::last_patched_line)
+                .max()
+                .map_or(gutter, |line| gutter.max(line.to_string().len()));
+            let mut snippet = false;
+            if let Some(span) = primary {
+                self.fmt_snippet(f, &annotations, span, gutter)?;
+                snippet = true;
             }
 
-            // Print colon and message.
-            write!(f, "{}", Reset)?;
-            write!(f, "{}: ", &self.styles.colon)?;
-            write!(f, "{}", Reset)?;
-            write!(f, "{}{}", &self.styles.message, &report.message)?;
+            // Print children.  Those with a location get a snippet of their own, the rest are
+            // listed beneath the gutter.
+            for child in &report.children {
+                let name = child.kind.name();
+                let style = match child.kind {
+                    ChildKind::Note => &self.styles.note,
+                    ChildKind::Help => &self.styles.help,
+                    ChildKind::Info => &self.styles.info,
+                };
 
-            // Print snippet, if applicable.
-            if let Some((location, line, column)) = line_column {
-                let line = location.file.source().lines().nth(line - 1).unwrap();
-
-                writeln!(f, "{}", Reset)?;
-                writeln!(f, "{}{}", &self.styles.snippet, &line,)?;
-
-                // Calculate cursor offset
-                let mut offset = 0;
-                let cursor_width = match line.chars().enumerate().find(|(idx, char)| {
-                    if *idx == column - 1 {
-                        true
-                    } else {
-                        offset += char.width().unwrap_or(1);
-                        false
+                if let Some(span) = &child.location {
+                    self.fmt_title(f, name, style, &child.message)?;
+                    self.fmt_snippet(f, &[(LabelKind::Primary, span, None)], span, gutter)?;
+                    snippet = true;
+                    continue;
+                }
+
+                if snippet {
+                    self.fmt_gutter_line(f, gutter)?;
+                    snippet = false;
+                }
+                self.fmt_footer(f, name, style, &child.message, gutter)?;
+            }
+
+            // Print suggestions.  Those without edits are listed beneath the gutter like children.
+            for suggestion in &report.suggestions {
+                if suggestion.edits.is_empty() {
+                    if snippet {
+                        self.fmt_gutter_line(f, gutter)?;
+                        snippet = false;
                     }
-                }) {
-                    Some((_, char)) => char.width().unwrap_or(1),
-                    None => 1,
-                };
+                    self.fmt_footer(f, "help", &self.styles.help, &suggestion.message, gutter)?;
+                } else {
+                    self.fmt_suggestion(f, suggestion, primary, gutter)?;
+                    snippet = true;
+                }
+            }
+        }
 
-                // Write cursor
-                write!(f, "{}", Reset)?;
-                write!(f, "{: <offset$}", "")?;
-                write!(
-                    f,
-                    "{}{:^<cursor_width$}",
-                    &self.styles.cursor,
-                    // &severity_style,
-                    ""
-                )?;
+        Ok(())
+    }
+}
+
+/// Sorts edits by their position, dropping any which overlap an earlier edit or don't fall on
+/// character boundaries.
+fn sorted_edits<'e>(edits: impl IntoIterator<Item = &'e Edit>) -> Vec<&'e Edit> {
+    let mut edits = edits
+        .into_iter()
+        .filter(|edit| {
+            let source = edit.span.file.source();
+            source.is_char_boundary(edit.span.start) && source.is_char_boundary(edit.span.end)
+        })
+        .collect::<Vec<_>>();
+    edits.sort_by_key(|edit| (edit.span.start, edit.span.end));
+
+    let mut end = 0;
+    edits.retain(|edit| {
+        // Insertions at the same position as another edit are kept, in order.
+        let keep = edit.span.start >= end;
+        if keep {
+            end = edit.span.end;
+        }
+        keep
+    });
+    edits
+}
+
+/// Applies sorted, non-overlapping edits to a region of the source.  Returns the patched region,
+/// along with the ranges of the patched region which were inserted by each edit.
+fn apply_edits(source: &str, region: Range<usize>, edits: &[&Edit]) -> (String, Vec<Range<usize>>) {
+    let mut patched = String::new();
+    let mut inserted = Vec::new();
+    let mut cursor = region.start;
+    for edit in edits {
+        patched.push_str(&source[cursor..edit.span.start]);
+        let start = patched.len();
+        patched.push_str(&edit.replacement);
+        inserted.push(start..patched.len());
+        cursor = edit.span.end;
+    }
+    patched.push_str(&source[cursor..region.end]);
+
+    (patched, inserted)
+}
+
+/// Returns the number of the last line changed by an edit.  An edit which removes the line break
+/// at the end of a line joins the next line onto it, changing that line too.
+fn last_edited_line(file: &File, edit: &Edit) -> usize {
+    let line = file.line_number(edit.span.end.saturating_sub(1).max(edit.span.start));
+    let next = file.line_number(edit.span.end);
+    let before = if edit.replacement.is_empty() {
+        &file.source()[..edit.span.start]
+    } else {
+        edit.replacement.as_str()
+    };
+
+    if next > line
+        && !file.line_range(next).unwrap().is_empty()
+        && !before.is_empty()
+        && !before.ends_with(['\r', '\n'])
+    {
+        next
+    } else {
+        line
+    }
+}
+
+/// An annotation on a single line of source code, measured in display columns.
+struct LineAnnotation<'a> {
+    kind: LabelKind,
+    start: usize,
+    end: usize,
+    message: Option<&'a str>,
+}
+
+/// A span covering multiple lines, which are connected by a bar in the left margin.
+struct MultilineAnnotation<'a> {
+    kind: LabelKind,
+    message: Option<&'a str>,
+    /// The column of the span's bar in the left margin.
+    depth: usize,
+    /// The span's first and last line numbers.
+    start_line: usize,
+    end_line: usize,
+    /// The display columns of the span's first and last characters.
+    start: usize,
+    end: usize,
+    slash: bool,
+}
+
+/// Iterates over the grapheme clusters of a line, along with their byte offsets, display columns
+/// and widths.  Tabs are expanded to the next multiple of the tab width.
+fn graphemes(line: &str, tab_width: usize) -> impl Iterator<Item = (usize, &str, usize, usize)> {
+    line.grapheme_indices(true)
+        .scan(0, move |column, (idx, grapheme)| {
+            let width = match grapheme {
+                "\t" if tab_width == 0 => 0,
+                "\t" => tab_width - *column % tab_width,
+                _ => grapheme.width(),
             };
-            writeln!(f, "{}", Reset)?;
+            let start = *column;
+            *column += width;
+            Some((idx, grapheme, start, width))
+        })
+}
+
+/// Returns the number of line breaks in some text, counting `\r\n` as one.
+fn line_breaks(text: &str) -> usize {
+    text.matches('\n').count() + text.matches('\r').count() - text.matches("\r\n").count()
+}
+
+/// Returns the display width of a line, with its tabs expanded.
+fn display_width(line: &str, tab_width: usize) -> usize {
+    graphemes(line, tab_width)
+        .last()
+        .map_or(0, |(_, _, column, width)| column + width)
+}
+
+/// Splits a line into lines of at most the given display width, breaking at spaces.  Words which
+/// are wider than the width are left whole.
+fn wrap(line: &str, width: usize) -> Vec<&str> {
+    let mut lines = Vec::new();
+    let mut rest = line;
+    while rest.width() > width {
+        // Break at the last space which fits, or else the first space after a word.
+        let mut column = 0;
+        let mut word = false;
+        let mut split = None;
+        for (idx, char) in rest.char_indices() {
+            if char == ' ' && word {
+                if column <= width || split.is_none() {
+                    split = Some(idx);
+                }
+                if column > width {
+                    break;
+                }
+            }
+            word |= char != ' ';
+            column += char.width().unwrap_or(0);
         }
 
+        let Some(split) = split else {
+            break;
+        };
+        lines.push(rest[..split].trim_end());
+        rest = rest[split..].trim_start();
+    }
+    lines.push(rest);
+    lines
+}
+
+/// Returns the display column of the grapheme cluster containing the given byte offset of a line.
+fn display_column(line: &str, offset: usize, tab_width: usize) -> usize {
+    let mut end = 0;
+    for (idx, grapheme, column, width) in graphemes(line, tab_width) {
+        if idx + grapheme.len() > offset {
+            return column;
+        }
+        end = column + width;
+    }
+    end
+}
+
+/// Returns the display columns covered by the given byte range of a line.  Ranges are widened to
+/// whole grapheme clusters, so that underlines match the cells drawn by a terminal.  Ranges which
+/// continue past the end of the line are cut off there, and every range covers at least one
+/// column.
+fn display_columns(line: &str, start: usize, end: usize, tab_width: usize) -> (usize, usize) {
+    let start_column = display_column(line, start, tab_width);
+    let width = graphemes(line, tab_width)
+        .filter(|(idx, grapheme, _, _)| *idx < end && idx + grapheme.len() > start)
+        .map(|(_, _, _, width)| width)
+        .sum::<usize>();
+
+    (start_column, start_column + width.max(1))
+}
+
+/// The display columns of a line which are shown in a snippet.  Lines wider than the window are
+/// trimmed to it, with `...` marking where they're cut.
+#[derive(Clone, Copy)]
+struct Window {
+    start: usize,
+    end: usize,
+}
+
+impl Window {
+    /// A window in which lines are shown in full.
+    const FULL: Window = Window {
+        start: 0,
+        end: usize::MAX,
+    };
+
+    /// Returns the column in the window of a display column of a line.  Columns outside the
+    /// window are moved to its edges.
+    fn column(self, column: usize) -> usize {
+        column.saturating_sub(self.start).min(self.end - self.start)
+    }
+
+    /// Returns the columns in the window of a range of display columns of a line, covering at
+    /// least one column.
+    fn columns(self, start: usize, end: usize) -> (usize, usize) {
+        let start = self.column(start);
+        (start, self.column(end).max(start + 1))
+    }
+}
+
+/// A grid of styled characters, used to lay out the annotations beneath a line of source code.
+#[derive(Default)]
+struct Canvas<'a> {
+    rows: Vec<Vec<(char, Option<&'a Style>)>>,
+}
+
+impl<'a> Canvas<'a> {
+    /// Places a character at the given row and column, growing the canvas if necessary.
+    fn put(&mut self, row: usize, column: usize, char: char, style: &'a Style) {
+        if self.rows.len() <= row {
+            self.rows.resize_with(row + 1, Vec::new);
+        }
+        let row = &mut self.rows[row];
+        if row.len() <= column {
+            row.resize(column + 1, (' ', None));
+        }
+        row[column] = (char, Some(style));
+    }
+
+    /// Places a string starting at the given row and column.
+    fn put_str(&mut self, row: usize, column: usize, text: &str, style: &'a Style) {
+        for (idx, char) in text.chars().enumerate() {
+            self.put(row, column + idx, char, style);
+        }
+    }
+
+    /// Places each line of a string on its own row, starting at the given row and column.
+    /// Returns the number of rows used.
+    fn put_lines(&mut self, row: usize, column: usize, text: &str, style: &'a Style) -> usize {
+        let lines = text.split("\r\n").flat_map(|line| line.split(['\r', '\n']));
+        let mut rows = 0;
+        for line in lines {
+            self.put_str(row + rows, column, line, style);
+            rows += 1;
+        }
+        rows
+    }
+
+    /// Returns `true` if nothing has been placed at the given row and column.
+    fn is_blank(&self, row: usize, column: usize) -> bool {
+        self.rows
+            .get(row)
+            .and_then(|row| row.get(column))
+            .is_none_or(|(char, _)| *char == ' ')
+    }
+
+    /// Writes the canvas, one line per row.
+    fn fmt(&self, f: &mut Output<'_, '_>) -> std::fmt::Result {
+        for row in &self.rows {
+            let mut style: Option<&Style> = None;
+            for &(char, cell_style) in row {
+                // Styles are compared by reference, as different styles may look the same.
+                let same = match (cell_style, style) {
+                    (Some(cell_style), Some(style)) => std::ptr::eq(cell_style, style),
+                    (cell_style, style) => cell_style.is_none() && style.is_none(),
+                };
+                if !same {
+                    if let Some(style) = style {
+                        f.close(style)?;
+                    }
+                    if let Some(cell_style) = cell_style {
+                        f.open(cell_style)?;
+                    }
+                    style = cell_style;
+                }
+                write!(f, "{char}")?;
+            }
+            if let Some(style) = style {
+                f.close(style)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The destination of a [Renderer], where styled text is marked either with ANSI escape codes or
+/// with markup elements.  Text written to markup is escaped.
+struct Output<'f, 's> {
+    f: &'f mut dyn std::fmt::Write,
+    markup: Option<Markup<'s>>,
+    class: Option<&'static str>,
+}
+
+/// Markup marking styled text with elements, such as `<span class="error">`.  Each element's class
+/// is the name of the field of [Styles] holding its style.
+#[derive(Clone, Copy)]
+struct Markup<'s> {
+    styles: &'s Styles,
+    element: &'static str,
+}
+
+impl<'f, 's> Output<'f, 's> {
+    /// Creates an [Output] which marks styled text with ANSI escape codes.
+    fn ansi(f: &'f mut dyn std::fmt::Write) -> Self {
+        Self {
+            f,
+            markup: None,
+            class: None,
+        }
+    }
+
+    /// Creates an [Output] which marks styled text with the given markup.
+    fn markup(f: &'f mut dyn std::fmt::Write, markup: Markup<'s>) -> Self {
+        Self {
+            f,
+            markup: Some(markup),
+            class: None,
+        }
+    }
+
+    /// Starts text in the given style.
+    fn open(&mut self, style: &Style) -> std::fmt::Result {
+        let Some(markup) = self.markup else {
+            return write!(self.f, "{style}");
+        };
+        self.class = markup.styles.class(style);
+        match self.class {
+            Some(class) => write!(self.f, "<{} class=\"{class}\">", markup.element),
+            None => Ok(()),
+        }
+    }
+
+    /// Ends text in the given style.
+    fn close(&mut self, style: &Style) -> std::fmt::Result {
+        let Some(markup) = self.markup else {
+            return write!(self.f, "{style:#}");
+        };
+        match self.class.take() {
+            Some(_) => write!(self.f, "</{}>", markup.element),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes text in the given style.
+    fn paint(&mut self, style: &Style, text: impl std::fmt::Display) -> std::fmt::Result {
+        self.open(style)?;
+        write!(self, "{text}")?;
+        self.close(style)
+    }
+}
+
+impl std::fmt::Write for Output<'_, '_> {
+    fn write_str(&mut self, text: &str) -> std::fmt::Result {
+        let Some(markup) = self.markup else {
+            return self.f.write_str(text);
+        };
+
+        for char in text.chars() {
+            match char {
+                '&' => self.f.write_str("&amp;")?,
+                '<' => self.f.write_str("&lt;")?,
+                '>' => self.f.write_str("&gt;")?,
+                '"' => self.f.write_str("&quot;")?,
+                '\'' => self.f.write_str("&#39;")?,
+                // Control characters aren't allowed in XML, even as character references.
+                '\0'..='\x08' | '\x0b' | '\x0c' | '\x0e'..='\x1f' | '\u{fffe}' | '\u{ffff}' => {
+                    self.f.write_char(char::REPLACEMENT_CHARACTER)?
+                }
+                // Elements are closed at the end of each line, and opened again on the next.
+                '\n' => match self.class {
+                    Some(class) => write!(
+                        self.f,
+                        "</{element}>\n<{element} class=\"{class}\">",
+                        element = markup.element
+                    )?,
+                    None => self.f.write_char('\n')?,
+                },
+                char => self.f.write_char(char)?,
+            }
+        }
         Ok(())
     }
 }
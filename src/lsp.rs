@@ -0,0 +1,150 @@
+//! Conversion of reports into Language Server Protocol types.
+//!
+//! Positions are counted in UTF-16 code units, as the protocol expects by default.  Spans are
+//! located in documents by a function mapping each [File] to its URI.
+
+use std::collections::HashMap;
+
+pub use lsp_types;
+use lsp_types::{
+    CodeAction, CodeActionKind, Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity,
+    NumberOrString, Position, TextEdit, Uri, WorkspaceEdit,
+};
+
+use crate::{Applicability, ColumnUnit, File, Location, Renderer, Report, Severity, Span};
+
+/// Converts a location into an LSP position.
+pub fn position(location: &Location) -> Position {
+    let (line, column) = location.line_column_in(ColumnUnit::Utf16);
+    Position::new(line as u32 - 1, column as u32 - 1)
+}
+
+/// Converts a span into an LSP range.
+pub fn range(span: &Span) -> lsp_types::Range {
+    lsp_types::Range::new(
+        position(&span.start_location()),
+        position(&span.end_location()),
+    )
+}
+
+/// Converts a report into an LSP diagnostic, ranging over its primary span.  Returns [None] if the
+/// report has no span.
+///
+/// Other labels and children with a location become related information, while the messages of
+/// children without one are appended to the diagnostic's message.
+///
+/// ```
+/// # use reporting::{error, lsp, File, Span};
+/// # use reporting::lsp::lsp_types::{DiagnosticSeverity, Position, Range, Uri};
+/// let file = File::new("test.txt", "let 😀 = stds;");
+/// let report = error!("Could not find `stds`").location(Span::new(file.clone(), 11..15));
+///
+/// let uri = |file: &File| format!("file:///{}", file.path()).parse::<Uri>().unwrap();
+/// let diagnostic = lsp::diagnostic(&report, uri).unwrap();
+/// assert_eq!(diagnostic.range, Range::new(Position::new(0, 9), Position::new(0, 13)));
+/// assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+/// ```
+pub fn diagnostic(report: &Report, uri: impl Fn(&File) -> Uri) -> Option<Diagnostic> {
+    let primary = report.primary_span()?;
+    let severity = match report.severity {
+        Severity::Note => DiagnosticSeverity::INFORMATION,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+        Severity::Error | Severity::Bug => DiagnosticSeverity::ERROR,
+    };
+
+    let mut message = report.message.clone();
+    for child in report
+        .children
+        .iter()
+        .filter(|child| child.location.is_none())
+    {
+        message.push_str(&format!("\n{}: {}", child.kind.name(), child.message));
+    }
+
+    // Every span other than the primary span is related information.  Unlabeled spans are
+    // described by the report's message.
+    let mut related = Vec::new();
+    let mut skip = Some(primary);
+    for (_, span, label) in Renderer::annotations(report) {
+        if skip == Some(span) {
+            skip = None;
+        } else {
+            related.push((span, label.unwrap_or(&report.message)));
+        }
+    }
+    related.extend(
+        report
+            .children
+            .iter()
+            .filter_map(|child| Some((child.location.as_ref()?, child.message.as_str()))),
+    );
+    let related = related
+        .into_iter()
+        .map(|(span, message)| DiagnosticRelatedInformation {
+            location: lsp_types::Location::new(uri(&span.file), range(span)),
+            message: message.to_string(),
+        })
+        .collect::<Vec<_>>();
+
+    Some(Diagnostic {
+        range: range(primary),
+        severity: Some(severity),
+        code: report.code.clone().map(NumberOrString::String),
+        message,
+        related_information: Some(related).filter(|related| !related.is_empty()),
+        ..Diagnostic::default()
+    })
+}
+
+/// Converts the suggestions of a report into LSP quick fixes for its diagnostic.  Returns no code
+/// actions if the report has no span.
+///
+/// Suggestions without edits are skipped.  Machine-applicable suggestions are marked as preferred.
+///
+/// ```
+/// # use reporting::{error, lsp, Applicability, Edit, File, Span, Suggestion};
+/// # use reporting::lsp::lsp_types::Uri;
+/// let file = File::new("test.txt", "import stds;");
+/// let report = error!("Could not find package `stds`")
+///     .location(Span::new(file.clone(), 7..11))
+///     .suggestion(
+///         Suggestion::new("Replace with `std`", Applicability::MachineApplicable)
+///             .edit(Edit::new(Span::new(file.clone(), 7..11), "std")),
+///     );
+///
+/// let uri = |file: &File| format!("file:///{}", file.path()).parse::<Uri>().unwrap();
+/// let actions = lsp::code_actions(&report, uri);
+/// assert_eq!(actions[0].title, "Replace with `std`");
+/// assert_eq!(actions[0].is_preferred, Some(true));
+/// ```
+pub fn code_actions(report: &Report, uri: impl Fn(&File) -> Uri) -> Vec<CodeAction> {
+    let Some(diagnostic) = diagnostic(report, &uri) else {
+        return Vec::new();
+    };
+
+    report
+        .suggestions
+        .iter()
+        .filter(|suggestion| !suggestion.edits.is_empty())
+        .map(|suggestion| {
+            // The key type is fixed by `WorkspaceEdit`, and a `Uri` isn't modified once it's a key.
+            #[allow(clippy::mutable_key_type)]
+            let mut changes = HashMap::<Uri, Vec<TextEdit>>::new();
+            for edit in &suggestion.edits {
+                changes
+                    .entry(uri(&edit.span.file))
+                    .or_default()
+                    .push(TextEdit::new(range(&edit.span), edit.replacement.clone()));
+            }
+
+            CodeAction {
+                title: suggestion.message.clone(),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(WorkspaceEdit::new(changes)),
+                is_preferred: Some(suggestion.applicability == Applicability::MachineApplicable),
+                ..CodeAction::default()
+            }
+        })
+        .collect()
+}
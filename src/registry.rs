@@ -0,0 +1,148 @@
+//! Long-form explanations of diagnostic codes.
+
+use std::collections::BTreeMap;
+
+use crate::Styles;
+
+/// A registry of long-form explanations for diagnostic codes, written in markdown.
+///
+/// ```
+/// # use reporting::{Registry, Styles};
+/// let registry = Registry::new().explanation(
+///     "E0412",
+///     "# Unresolved package\n\nA package was imported which doesn't exist:\n\n```\nimport stds;\n```",
+/// );
+///
+/// let styles = Styles::plain();
+/// let explanation = registry.explain("E0412", &styles).unwrap();
+/// assert_eq!(
+///     explanation.to_string(),
+///     "Unresolved package\n\nA package was imported which doesn't exist:\n\n    import stds;\n"
+/// );
+/// assert!(registry.explain("E0001", &styles).is_none());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Registry {
+    explanations: BTreeMap<String, String>,
+}
+
+impl Registry {
+    /// Creates a new, empty [Registry].
+    pub const fn new() -> Self {
+        Self {
+            explanations: BTreeMap::new(),
+        }
+    }
+
+    /// Adds an explanation for a code to this registry, replacing any previous explanation.
+    pub fn explanation(mut self, code: impl Into<String>, markdown: impl Into<String>) -> Self {
+        self.insert(code, markdown);
+        self
+    }
+
+    /// Inserts an explanation for a code into this registry, returning the previous explanation.
+    pub fn insert(
+        &mut self,
+        code: impl Into<String>,
+        markdown: impl Into<String>,
+    ) -> Option<String> {
+        self.explanations.insert(code.into(), markdown.into())
+    }
+
+    /// Returns the markdown explanation of a code, if it has one.
+    pub fn get(&self, code: &str) -> Option<&str> {
+        self.explanations.get(code).map(String::as_str)
+    }
+
+    /// Returns every code with an explanation, in order.
+    pub fn codes(&self) -> impl Iterator<Item = &str> {
+        self.explanations.keys().map(String::as_str)
+    }
+
+    /// Creates a renderer for the explanation of a code, if it has one.
+    pub fn explain<'a>(&'a self, code: &str, styles: &'a Styles) -> Option<Explanation<'a>> {
+        self.get(code)
+            .map(|markdown| Explanation::new(styles, markdown))
+    }
+}
+
+/// A renderer for a markdown explanation.
+///
+/// Headings, code blocks and inline code are styled, and their markup removed.  Code blocks are
+/// indented by four spaces.  Any other text is written as it is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Explanation<'a> {
+    styles: &'a Styles,
+    markdown: &'a str,
+}
+
+impl<'a> Explanation<'a> {
+    /// Creates a new [Explanation] with the given styles and markdown.
+    pub const fn new(styles: &'a Styles, markdown: &'a str) -> Self {
+        Self { styles, markdown }
+    }
+
+    /// Writes a line of text, styling any inline code.
+    fn fmt_text(&self, f: &mut std::fmt::Formatter<'_>, line: &str) -> std::fmt::Result {
+        // Backticks alternate between text and code.  An unmatched backtick is written as it is.
+        let pieces = line.split('`').collect::<Vec<_>>();
+        let matched = pieces.len() - (1 - pieces.len() % 2);
+        for (i, piece) in pieces.into_iter().enumerate() {
+            if i >= matched {
+                write!(f, "`{piece}")?;
+            } else if i % 2 == 1 {
+                let style = &self.styles.location;
+                write!(f, "{style}{piece}{style:#}")?;
+            } else {
+                write!(f, "{piece}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> std::fmt::Display for Explanation<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut fence = None;
+        for line in self.markdown.lines() {
+            let trimmed = line.trim_start();
+
+            // Code blocks are fenced by at least three backticks or tildes, and closed by at least
+            // as many of the same.
+            let marker = ['`', '~']
+                .into_iter()
+                .map(|c| &trimmed[..trimmed.len() - trimmed.trim_start_matches(c).len()])
+                .find(|marker| marker.len() >= 3);
+            if let Some(marker) = marker {
+                match fence {
+                    None => {
+                        fence = Some(marker);
+                        continue;
+                    }
+                    Some(open) if marker.starts_with(open) && trimmed.trim_end() == marker => {
+                        fence = None;
+                        continue;
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            if fence.is_some() {
+                let style = &self.styles.snippet;
+                writeln!(f, "    {style}{line}{style:#}")?;
+            } else if let Some(heading) = trimmed
+                .strip_prefix('#')
+                .map(|heading| heading.trim_start_matches('#'))
+                .filter(|heading| heading.is_empty() || heading.starts_with(' '))
+            {
+                let style = &self.styles.message;
+                writeln!(f, "{style}{}{style:#}", heading.trim())?;
+            } else {
+                self.fmt_text(f, line)?;
+                writeln!(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
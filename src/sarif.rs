@@ -0,0 +1,286 @@
+//! Diagnostics in the SARIF 2.1.0 format.
+
+use crate::{json::Value, Registry, Renderer, Report, Severity, Span};
+
+/// A renderer for diagnostic reports as a SARIF 2.1.0 log, with a single run of one tool.
+///
+/// Each report becomes a result, with its code as the `ruleId` and its primary span as its
+/// location.  Other labels and children with a location become related locations, while the
+/// messages of children without one are appended to the result's message.  Suggestions become
+/// fixes.  Columns are counted in characters, and offsets in bytes.
+///
+/// ```
+/// # use reporting::{error, File, SarifRenderer, Span};
+/// let file = File::new("src/main.txt", "import stds;");
+/// let reports = [error!("Could not find package `stds`")
+///     .code("E0412")
+///     .location(Span::new(file.clone(), 7..11))];
+///
+/// let sarif = SarifRenderer::new("compiler", &reports).to_string();
+/// assert!(sarif.contains(r#""results":[{"ruleId":"E0412","level":"error","message":{"text":"Could not find package `stds`"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"src/main.txt"},"region":{"startLine":1,"startColumn":8,"endLine":1,"endColumn":12,"byteOffset":7,"byteLength":4}}}]"#));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SarifRenderer<'a> {
+    tool: &'a str,
+    version: Option<&'a str>,
+    reports: &'a [Report],
+    registry: Option<&'a Registry>,
+}
+
+impl<'a> SarifRenderer<'a> {
+    /// Creates a new [SarifRenderer] for reports produced by the named tool.
+    pub const fn new(tool: &'a str, reports: &'a [Report]) -> Self {
+        Self {
+            tool,
+            version: None,
+            reports,
+            registry: None,
+        }
+    }
+
+    /// Sets the version of the tool which produced the reports.  Defaults to none.
+    pub fn version(mut self, version: &'a str) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Sets the registry from which rules are described.  Defaults to none, in which case rules
+    /// only have an ID.
+    pub fn registry(mut self, registry: &'a Registry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+}
+
+impl<'a> SarifRenderer<'a> {
+    /// Converts the codes of the reports into rules, in the order they first appear.
+    fn rules(&self) -> Vec<Value> {
+        let mut codes = Vec::new();
+        for code in self
+            .reports
+            .iter()
+            .filter_map(|report| report.code.as_deref())
+        {
+            if !codes.contains(&code) {
+                codes.push(code);
+            }
+        }
+
+        codes
+            .into_iter()
+            .map(|code| {
+                let mut rule = vec![("id", code.into())];
+                if let Some(markdown) = self.registry.and_then(|registry| registry.get(code)) {
+                    rule.push((
+                        "fullDescription",
+                        Value::Object(vec![
+                            ("text", markdown.into()),
+                            ("markdown", markdown.into()),
+                        ]),
+                    ));
+                }
+                Value::Object(rule)
+            })
+            .collect()
+    }
+
+    /// Converts a report into a SARIF result.
+    fn result(report: &Report) -> Value {
+        let level = match report.severity {
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error | Severity::Bug => "error",
+        };
+
+        let mut message = report.message.clone();
+        for child in report
+            .children
+            .iter()
+            .filter(|child| child.location.is_none())
+        {
+            message.push_str(&format!("\n{}: {}", child.kind.name(), child.message));
+        }
+
+        let mut result = Vec::new();
+        if let Some(code) = &report.code {
+            result.push(("ruleId", code.as_str().into()));
+        }
+        result.push(("level", level.into()));
+        result.push(("message", text(&message)));
+
+        // The primary span is the result's location, and every other span a related location.
+        let mut primary = report.primary_span();
+        let mut locations = Vec::new();
+        let mut related = Vec::new();
+        for (_, span, label) in Renderer::annotations(report) {
+            if primary == Some(span) {
+                locations.push(location(None, span, label));
+                primary = None;
+            } else {
+                related.push((span, label));
+            }
+        }
+        related.extend(
+            report
+                .children
+                .iter()
+                .filter_map(|child| Some((child.location.as_ref()?, Some(child.message.as_str())))),
+        );
+        if !locations.is_empty() {
+            result.push(("locations", Value::Array(locations)));
+        }
+        if !related.is_empty() {
+            let related = related
+                .into_iter()
+                .enumerate()
+                .map(|(id, (span, message))| location(Some(id), span, message))
+                .collect();
+            result.push(("relatedLocations", Value::Array(related)));
+        }
+
+        let fixes = report
+            .suggestions
+            .iter()
+            .filter(|suggestion| !suggestion.edits.is_empty())
+            .map(|suggestion| {
+                // Edits are grouped into a change per file, in the order the files first appear.
+                let mut files = Vec::new();
+                for edit in &suggestion.edits {
+                    if !files.contains(&&edit.span.file) {
+                        files.push(&edit.span.file);
+                    }
+                }
+
+                let changes = files
+                    .into_iter()
+                    .map(|file| {
+                        let replacements = suggestion
+                            .edits
+                            .iter()
+                            .filter(|edit| edit.span.file == *file)
+                            .map(|edit| {
+                                Value::Object(vec![
+                                    ("deletedRegion", region(&edit.span)),
+                                    ("insertedContent", text(&edit.replacement)),
+                                ])
+                            })
+                            .collect();
+                        Value::Object(vec![
+                            ("artifactLocation", artifact_location(file.path())),
+                            ("replacements", Value::Array(replacements)),
+                        ])
+                    })
+                    .collect();
+                Value::Object(vec![
+                    ("description", text(&suggestion.message)),
+                    ("artifactChanges", Value::Array(changes)),
+                ])
+            })
+            .collect::<Vec<_>>();
+        if !fixes.is_empty() {
+            result.push(("fixes", Value::Array(fixes)));
+        }
+
+        Value::Object(result)
+    }
+}
+
+impl<'a> std::fmt::Display for SarifRenderer<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut driver = vec![("name", self.tool.into())];
+        if let Some(version) = self.version {
+            driver.push(("version", version.into()));
+        }
+        driver.push(("rules", Value::Array(self.rules())));
+
+        let run = Value::Object(vec![
+            (
+                "tool",
+                Value::Object(vec![("driver", Value::Object(driver))]),
+            ),
+            ("columnKind", "unicodeCodePoints".into()),
+            (
+                "results",
+                Value::Array(self.reports.iter().map(Self::result).collect()),
+            ),
+        ]);
+        let log = Value::Object(vec![
+            (
+                "$schema",
+                "https://json.schemastore.org/sarif-2.1.0.json".into(),
+            ),
+            ("version", "2.1.0".into()),
+            ("runs", Value::Array(vec![run])),
+        ]);
+
+        writeln!(f, "{log}")
+    }
+}
+
+/// Creates a SARIF message with the given text.
+fn text(text: &str) -> Value {
+    Value::Object(vec![("text", text.into())])
+}
+
+/// Converts a span into a SARIF location, with an optional ID and message.
+fn location(id: Option<usize>, span: &Span, message: Option<&str>) -> Value {
+    let mut location = Vec::new();
+    if let Some(id) = id {
+        location.push(("id", id.into()));
+    }
+    location.push((
+        "physicalLocation",
+        Value::Object(vec![
+            ("artifactLocation", artifact_location(span.file.path())),
+            ("region", region(span)),
+        ]),
+    ));
+    if let Some(message) = message {
+        location.push(("message", text(message)));
+    }
+    Value::Object(location)
+}
+
+/// Converts a path into a SARIF artifact location, with the path as a URI reference.
+fn artifact_location(path: &str) -> Value {
+    Value::Object(vec![("uri", uri(path).into())])
+}
+
+/// Converts a span into a SARIF region, with both its line and column numbers and byte offsets.
+fn region(span: &Span) -> Value {
+    let (start_line, start_column) = span.start_location().line_column();
+    let (end_line, end_column) = span.end_location().line_column();
+    Value::Object(vec![
+        ("startLine", start_line.into()),
+        ("startColumn", start_column.into()),
+        ("endLine", end_line.into()),
+        ("endColumn", end_column.into()),
+        ("byteOffset", span.start.into()),
+        ("byteLength", span.len().into()),
+    ])
+}
+
+/// Converts a path into a URI reference.  Backslashes are treated as separators, absolute paths
+/// become `file` URIs, and any characters not allowed in a URI are percent-encoded.
+fn uri(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut uri = if path.starts_with('/') {
+        String::from("file://")
+    } else if path.as_bytes().first().is_some_and(u8::is_ascii_alphabetic)
+        && path.as_bytes().get(1) == Some(&b':')
+    {
+        // A Windows path with a drive letter.
+        String::from("file:///")
+    } else {
+        String::new()
+    };
+
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/:@!$&'()*+,;=".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    uri
+}
@@ -0,0 +1,127 @@
+//! Diagnostics rendered as SVG images.
+
+use anstyle::{AnsiColor, Color};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    html::{hex, stylesheet},
+    Markup, Output, Renderer, Styles,
+};
+
+/// The size of the font, in pixels.
+const FONT_SIZE: usize = 14;
+/// The height of a line, in pixels.
+const LINE_HEIGHT: usize = 18;
+/// The space around the reports, in pixels.
+const PADDING: usize = 10;
+
+/// A renderer for diagnostic reports as an SVG image, created by [`Renderer::svg`].
+///
+/// Reports are rendered with the same layout as the text [Renderer], as lines of 14 pixel
+/// monospace text in rows 18 pixels apart.  Styled text is wrapped in `<tspan>` elements, classed
+/// and styled like those of the [HtmlRenderer](crate::HtmlRenderer).  The image is sized to fit
+/// the reports, assuming each character is 0.6 times as wide as it's high, as in most monospace
+/// fonts.  The same reports always render to the same image, so it can be used as a snapshot.
+///
+/// ```
+/// # use reporting::{error, File, Renderer, Span, Styles};
+/// let file = File::new("test.txt", "import stds;");
+/// let reports = [error!("Could not find package `stds`").location(Span::new(file.clone(), 7..11))];
+///
+/// let styles = Styles::styled();
+/// let svg = Renderer::new(&styles, &reports).svg().to_string();
+/// assert!(svg.starts_with(
+///     "<svg xmlns=\"http://www.w3.org/2000/svg\" class=\"reporting\" width=\"323\" height=\"110\" \
+///      viewBox=\"0 0 323 110\" xml:space=\"preserve\">\n"
+/// ));
+/// assert!(svg.contains(
+///     "<text x=\"10\" y=\"24\"><tspan class=\"error\">error</tspan><tspan class=\"colon\">:\
+///      </tspan> <tspan class=\"message\">Could not find package `stds`</tspan></text>\n"
+/// ));
+/// assert!(svg.ends_with("</svg>\n"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SvgRenderer<'a> {
+    renderer: Renderer<'a>,
+    foreground: Color,
+    background: Option<Color>,
+}
+
+impl<'a> SvgRenderer<'a> {
+    /// Creates a new [SvgRenderer] with the layout and settings of the given renderer.
+    pub const fn new(renderer: Renderer<'a>) -> Self {
+        Self {
+            renderer,
+            foreground: Color::Ansi(AnsiColor::White),
+            background: Some(Color::Ansi(AnsiColor::Black)),
+        }
+    }
+
+    /// Sets the color of unstyled text.  Defaults to [`AnsiColor::White`].
+    pub fn foreground(mut self, foreground: Color) -> Self {
+        self.foreground = foreground;
+        self
+    }
+
+    /// Sets the color of the background, or [None] for a transparent background.  Defaults to
+    /// [`AnsiColor::Black`].
+    pub fn background(mut self, background: Option<Color>) -> Self {
+        self.background = background;
+        self
+    }
+}
+
+impl<'a> std::fmt::Display for SvgRenderer<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let styles = self.renderer.styles;
+        let mut text = String::new();
+        let markup = Markup {
+            styles,
+            element: "tspan",
+        };
+        self.renderer
+            .fmt_reports(&mut Output::markup(&mut text, markup))?;
+
+        // The image is sized from the reports as plain text.
+        static PLAIN: Styles = Styles::plain();
+        let plain = Renderer {
+            styles: &PLAIN,
+            ..self.renderer.clone()
+        }
+        .to_string();
+        let columns = plain.lines().map(str::width).max().unwrap_or(0);
+        let width = 2 * PADDING + (columns * FONT_SIZE * 3).div_ceil(5);
+        let height = 2 * PADDING + plain.lines().count() * LINE_HEIGHT;
+
+        writeln!(
+            f,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" class=\"reporting\" width=\"{width}\" \
+             height=\"{height}\" viewBox=\"0 0 {width} {height}\" xml:space=\"preserve\">"
+        )?;
+        writeln!(f, "<style>")?;
+        writeln!(
+            f,
+            "svg.reporting text {{ fill: {}; font: {FONT_SIZE}px ui-monospace, SFMono-Regular, \
+             Menlo, Consolas, \"DejaVu Sans Mono\", monospace; white-space: pre; }}",
+            hex(self.foreground)
+        )?;
+        write!(f, "{}", stylesheet(styles, "svg.reporting", "fill"))?;
+        writeln!(f, "</style>")?;
+        if let Some(background) = self.background {
+            writeln!(
+                f,
+                "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+                hex(background)
+            )?;
+        }
+
+        // Each line is placed on the baseline of its row, leaving blank lines out.
+        for (i, line) in text.lines().enumerate() {
+            if !line.is_empty() {
+                let y = PADDING + (i + 1) * LINE_HEIGHT - 4;
+                writeln!(f, "<text x=\"{PADDING}\" y=\"{y}\">{line}</text>")?;
+            }
+        }
+        writeln!(f, "</svg>")
+    }
+}
@@ -0,0 +1,312 @@
+//! Themes: [Styles] serialized as TOML or JSON.
+//!
+//! A theme maps each field of [Styles] to a style, made up of a foreground color `fg`, a
+//! background color `bg`, an `underline` color and a list of `effects`.  Colors are written as
+//! the name of an ANSI color, such as `"red"` or `"bright_red"`, an index into the 256-color
+//! palette, or an RGB color such as `"#ff5555"`.  Fields which are left out are unstyled.
+//!
+//! ```toml
+//! [error]
+//! fg = "#ff6b6b"
+//! effects = ["bold"]
+//!
+//! [warning]
+//! fg = 220
+//! effects = ["bold", "underline"]
+//!
+//! [gutter]
+//! fg = "bright_blue"
+//! ```
+
+use anstyle::{Ansi256Color, AnsiColor, Color, Effects, RgbColor, Style};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Styles;
+
+/// The names of effects, as written in themes.
+const EFFECTS: [(&str, Effects); 12] = [
+    ("bold", Effects::BOLD),
+    ("dimmed", Effects::DIMMED),
+    ("italic", Effects::ITALIC),
+    ("underline", Effects::UNDERLINE),
+    ("double_underline", Effects::DOUBLE_UNDERLINE),
+    ("curly_underline", Effects::CURLY_UNDERLINE),
+    ("dotted_underline", Effects::DOTTED_UNDERLINE),
+    ("dashed_underline", Effects::DASHED_UNDERLINE),
+    ("blink", Effects::BLINK),
+    ("invert", Effects::INVERT),
+    ("hidden", Effects::HIDDEN),
+    ("strikethrough", Effects::STRIKETHROUGH),
+];
+
+/// The names of ANSI colors, as written in themes.
+const COLORS: [(&str, AnsiColor); 16] = [
+    ("black", AnsiColor::Black),
+    ("red", AnsiColor::Red),
+    ("green", AnsiColor::Green),
+    ("yellow", AnsiColor::Yellow),
+    ("blue", AnsiColor::Blue),
+    ("magenta", AnsiColor::Magenta),
+    ("cyan", AnsiColor::Cyan),
+    ("white", AnsiColor::White),
+    ("bright_black", AnsiColor::BrightBlack),
+    ("bright_red", AnsiColor::BrightRed),
+    ("bright_green", AnsiColor::BrightGreen),
+    ("bright_yellow", AnsiColor::BrightYellow),
+    ("bright_blue", AnsiColor::BrightBlue),
+    ("bright_magenta", AnsiColor::BrightMagenta),
+    ("bright_cyan", AnsiColor::BrightCyan),
+    ("bright_white", AnsiColor::BrightWhite),
+];
+
+/// An error encountered while loading a theme.
+#[derive(Debug)]
+pub enum ThemeError {
+    /// The theme isn't valid TOML, or doesn't describe [Styles].
+    Toml(toml::de::Error),
+    /// The theme isn't valid JSON, or doesn't describe [Styles].
+    Json(serde_json::Error),
+}
+
+impl std::fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThemeError::Toml(error) => write!(f, "invalid TOML theme: {error}"),
+            ThemeError::Json(error) => write!(f, "invalid JSON theme: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ThemeError::Toml(error) => Some(error),
+            ThemeError::Json(error) => Some(error),
+        }
+    }
+}
+
+impl Styles {
+    /// Loads a set of [Styles] from a TOML theme.
+    ///
+    /// ```
+    /// # use reporting::{anstyle::{Ansi256Color, Style}, Styles};
+    /// let styles = Styles::from_toml("[warning]\nfg = 220\neffects = [\"bold\"]")?;
+    /// let warning = Style::new().fg_color(Some(Ansi256Color(220).into())).bold();
+    /// assert_eq!(styles.warning, warning);
+    /// assert_eq!(styles.error, Style::new());
+    ///
+    /// let dark = Styles::dark();
+    /// assert_eq!(Styles::from_toml(&toml::to_string(&dark).unwrap())?, dark);
+    /// # Ok::<(), reporting::ThemeError>(())
+    /// ```
+    pub fn from_toml(theme: &str) -> Result<Self, ThemeError> {
+        toml::from_str(theme).map_err(ThemeError::Toml)
+    }
+
+    /// Loads a set of [Styles] from a JSON theme.
+    ///
+    /// ```
+    /// # use reporting::{anstyle::{RgbColor, Style}, Styles};
+    /// let styles = Styles::from_json(r##"{"error": {"fg": "#ff6b6b", "effects": ["bold"]}}"##)?;
+    /// let error = Style::new().fg_color(Some(RgbColor(255, 107, 107).into())).bold();
+    /// assert_eq!(styles.error, error);
+    /// assert!(Styles::from_json(r#"{"error": {"fg": "rouge"}}"#).is_err());
+    ///
+    /// for styles in [Styles::styled(), Styles::light(), Styles::high_contrast()] {
+    ///     assert_eq!(Styles::from_json(&serde_json::to_string(&styles).unwrap())?, styles);
+    /// }
+    /// # Ok::<(), reporting::ThemeError>(())
+    /// ```
+    pub fn from_json(theme: &str) -> Result<Self, ThemeError> {
+        serde_json::from_str(theme).map_err(ThemeError::Json)
+    }
+}
+
+/// Declares the serialized form of [Styles], with a field for each of its styles.
+macro_rules! theme {
+    ($($field:ident),* $(,)?) => {
+        /// The serialized form of [Styles].
+        #[derive(Default, Serialize, Deserialize)]
+        #[serde(default, deny_unknown_fields)]
+        struct Theme {
+            $(
+                #[serde(skip_serializing_if = "StyleDef::is_plain")]
+                $field: StyleDef,
+            )*
+        }
+
+        impl From<&Styles> for Theme {
+            fn from(styles: &Styles) -> Self {
+                Self {
+                    $($field: StyleDef::from(styles.$field),)*
+                }
+            }
+        }
+
+        impl From<Theme> for Styles {
+            fn from(theme: Theme) -> Self {
+                Self {
+                    $($field: theme.$field.into(),)*
+                }
+            }
+        }
+    };
+}
+
+theme!(
+    location,
+    bug,
+    error,
+    warning,
+    note,
+    help,
+    info,
+    addition,
+    removal,
+    colon,
+    message,
+    snippet,
+    cursor,
+    secondary_cursor,
+    gutter,
+);
+
+impl Serialize for Styles {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Theme::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Styles {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Theme::deserialize(deserializer).map(Styles::from)
+    }
+}
+
+/// The serialized form of a [Style].
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct StyleDef {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fg: Option<ColorDef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bg: Option<ColorDef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    underline: Option<ColorDef>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    effects: Vec<EffectDef>,
+}
+
+impl StyleDef {
+    /// Returns `true` if this style has no colors or effects.
+    fn is_plain(&self) -> bool {
+        self.fg.is_none()
+            && self.bg.is_none()
+            && self.underline.is_none()
+            && self.effects.is_empty()
+    }
+}
+
+impl From<Style> for StyleDef {
+    fn from(style: Style) -> Self {
+        Self {
+            fg: style.get_fg_color().map(ColorDef),
+            bg: style.get_bg_color().map(ColorDef),
+            underline: style.get_underline_color().map(ColorDef),
+            effects: style.get_effects().iter().map(EffectDef).collect(),
+        }
+    }
+}
+
+impl From<StyleDef> for Style {
+    fn from(style: StyleDef) -> Self {
+        let effects = style
+            .effects
+            .into_iter()
+            .fold(Effects::new(), |effects, EffectDef(effect)| {
+                effects | effect
+            });
+        Style::new()
+            .fg_color(style.fg.map(|ColorDef(color)| color))
+            .bg_color(style.bg.map(|ColorDef(color)| color))
+            .underline_color(style.underline.map(|ColorDef(color)| color))
+            .effects(effects)
+    }
+}
+
+/// The serialized form of a [Color]: the name of an ANSI color, an index into the 256-color
+/// palette, or an RGB color written as `#rrggbb`.
+struct ColorDef(Color);
+
+impl Serialize for ColorDef {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Color::Ansi(color) => {
+                let (name, _) = COLORS.iter().find(|(_, ansi)| *ansi == color).unwrap();
+                serializer.serialize_str(name)
+            }
+            Color::Ansi256(Ansi256Color(index)) => serializer.serialize_u8(index),
+            Color::Rgb(RgbColor(r, g, b)) => {
+                serializer.serialize_str(&format!("#{r:02x}{g:02x}{b:02x}"))
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ColorDef {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Index(u8),
+            Name(String),
+        }
+
+        let name = match Repr::deserialize(deserializer)? {
+            Repr::Index(index) => return Ok(ColorDef(Ansi256Color(index).into())),
+            Repr::Name(name) => name,
+        };
+
+        if let Some((_, color)) = COLORS.iter().find(|(color, _)| *color == name) {
+            return Ok(ColorDef((*color).into()));
+        }
+
+        let rgb = name
+            .strip_prefix('#')
+            .filter(|hex| hex.len() == 6 && hex.is_ascii())
+            .and_then(|hex| {
+                let channel = |i| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+                Some(RgbColor(channel(0)?, channel(2)?, channel(4)?))
+            });
+        rgb.map(|rgb| ColorDef(rgb.into())).ok_or_else(|| {
+            serde::de::Error::custom(format!(
+                "invalid color `{name}`, expected an ANSI color name, a 256-color index or `#rrggbb`"
+            ))
+        })
+    }
+}
+
+/// The serialized form of a single effect, written as its name.
+struct EffectDef(Effects);
+
+impl Serialize for EffectDef {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (name, _) = EFFECTS
+            .iter()
+            .find(|(_, effect)| *effect == self.0)
+            .unwrap();
+        serializer.serialize_str(name)
+    }
+}
+
+impl<'de> Deserialize<'de> for EffectDef {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        EFFECTS
+            .iter()
+            .find(|(effect, _)| *effect == name)
+            .map(|(_, effect)| EffectDef(*effect))
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown effect `{name}`")))
+    }
+}